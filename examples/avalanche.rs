@@ -0,0 +1,128 @@
+//! Measures how well `AHasher` avalanches: for every input bit and every output bit, the probability that flipping
+//! the input bit flips the output bit. Ideally every probability is 0.5.
+//!
+//! The full matrix is written to stdout as CSV (`input,input_bit,output_bit,probability`), and a summary of the
+//! deviations from 0.5 is written to stderr. This is intended for comparing alternatives when changing the mixing
+//! constants such as `SHUFFLE_MASK` in `operations.rs` or `ROT` in `fallback_hash.rs`.
+//!
+//! ```text
+//! cargo run --release --example avalanche [samples] > avalanche.csv
+//! RUSTFLAGS="-C target-feature=+aes" cargo run --release --example avalanche > avalanche.csv
+//! ```
+//!
+//! The first measures the fallback hasher and the second the AES hasher. Each sample uses a new random key, and
+//! defaults to 10000 samples.
+use ahash::AHasher;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::hash::Hasher;
+use std::io::{self, BufWriter, Write};
+
+/// The inputs which are measured. There is one byte length for each of the size ranges handled by a different branch
+/// of `AHasher::write`, using the largest length in the range so every branch reads all of its input.
+const INPUTS: &[(&str, Input)] = &[
+    ("write 1-7 bytes", Input::Bytes(7)),
+    ("write 8-16 bytes", Input::Bytes(16)),
+    ("write 17-32 bytes", Input::Bytes(32)),
+    ("write 33-64 bytes", Input::Bytes(64)),
+    ("write 65+ bytes", Input::Bytes(128)),
+    ("write_u64", Input::U64),
+];
+
+#[derive(Clone, Copy)]
+enum Input {
+    Bytes(usize),
+    U64,
+}
+
+impl Input {
+    fn len(self) -> usize {
+        match self {
+            Input::Bytes(len) => len,
+            Input::U64 => 8,
+        }
+    }
+
+    fn hash(self, key: (u128, u128), data: &[u8]) -> u64 {
+        let mut hasher = AHasher::new_with_keys(key.0, key.1);
+        match self {
+            Input::Bytes(_) => Hasher::write(&mut hasher, data),
+            Input::U64 => {
+                let mut value = [0; 8];
+                value.copy_from_slice(data);
+                hasher.write_u64(u64::from_le_bytes(value));
+            }
+        }
+        hasher.finish()
+    }
+}
+
+/// Returns the number of times each output bit flipped when each input bit was flipped, indexed by
+/// `input_bit * 64 + output_bit`.
+fn flip_counts(input: Input, samples: u64, rng: &mut StdRng) -> Vec<u64> {
+    let bits = input.len() * 8;
+    let mut counts = vec![0_u64; bits * 64];
+    let mut data = vec![0_u8; input.len()];
+    for _ in 0..samples {
+        let key = (rng.gen(), rng.gen());
+        rng.fill(&mut data[..]);
+        let base = input.hash(key, &data);
+        for bit in 0..bits {
+            data[bit / 8] ^= 1 << (bit % 8);
+            let diff = base ^ input.hash(key, &data);
+            data[bit / 8] ^= 1 << (bit % 8);
+            for (output_bit, count) in counts[bit * 64..(bit + 1) * 64].iter_mut().enumerate() {
+                *count += (diff >> output_bit) & 1;
+            }
+        }
+    }
+    counts
+}
+
+fn main() -> io::Result<()> {
+    let samples: u64 = match std::env::args().nth(1) {
+        Some(arg) => arg.parse().expect("The argument should be the number of samples"),
+        None => 10_000,
+    };
+    let backend = if cfg!(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "aes")) {
+        "AES"
+    } else if cfg!(feature = "runtime-dispatch") {
+        "runtime selected"
+    } else {
+        "fallback"
+    };
+    eprintln!("Measuring the {} hasher with {} samples per input bit", backend, samples);
+    eprintln!(
+        "(The standard deviation of each probability due to sampling is {:.5})",
+        0.5 / (samples as f64).sqrt()
+    );
+
+    let mut rng = StdRng::seed_from_u64(0xA7A1_A2C3);
+    let mut out = BufWriter::new(io::stdout());
+    writeln!(out, "input,input_bit,output_bit,probability")?;
+    for (name, input) in INPUTS {
+        let counts = flip_counts(*input, samples, &mut rng);
+        let mut max_deviation = 0.0_f64;
+        let mut worst = 0;
+        let mut total_deviation = 0.0;
+        for (i, count) in counts.iter().enumerate() {
+            let probability = *count as f64 / samples as f64;
+            writeln!(out, "{},{},{},{:.6}", name, i / 64, i % 64, probability)?;
+            let deviation = (probability - 0.5).abs();
+            total_deviation += deviation;
+            if deviation > max_deviation {
+                max_deviation = deviation;
+                worst = i;
+            }
+        }
+        eprintln!(
+            "{:<18} max deviation {:.5} (input bit {:>4}, output bit {:>2}), mean deviation {:.5}",
+            name,
+            max_deviation,
+            worst / 64,
+            worst % 64,
+            total_deviation / counts.len() as f64
+        );
+    }
+    out.flush()
+}
@@ -0,0 +1,158 @@
+/*
+ * C bindings for aHash.
+ *
+ * Generated from src/lib.rs by cbindgen. Do not edit this file; after changing src/lib.rs regenerate it with:
+ *     cbindgen --config cbindgen.toml --output include/ahash.h
+ */
+
+#ifndef AHASH_H
+#define AHASH_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * A hasher which is in the process of hashing some data. (An `AHasher`)
+ */
+typedef struct ahash_hasher_t ahash_hasher_t;
+
+/**
+ * The keys used to create hashers. (A `RandomState`)
+ */
+typedef struct ahash_state_t ahash_state_t;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Creates a state from four keys. (`RandomState::with_keys`)
+ */
+ahash_state_t *ahash_state_new(uint64_t k0, uint64_t k1, uint64_t k2, uint64_t k3);
+
+/**
+ * Creates a state from two seeds. (`RandomState::with_seeds`)
+ */
+ahash_state_t *ahash_state_from_seeds(uint64_t k0, uint64_t k1);
+
+/**
+ * Creates a state with random keys. (`RandomState::new`)
+ */
+ahash_state_t *ahash_state_new_random(void);
+
+/**
+ * Creates a state from the 32 bytes produced by `ahash_state_to_bytes` or `RandomState::to_bytes`.
+ *
+ * # Safety
+ * `bytes` must point to 32 readable bytes.
+ */
+ahash_state_t *ahash_state_from_bytes(const uint8_t *bytes);
+
+/**
+ * Writes the keys of `state` as 32 bytes to `out`. (`RandomState::to_bytes`)
+ *
+ * # Safety
+ * `state` must be a valid state and `out` must point to 32 writable bytes.
+ */
+void ahash_state_to_bytes(const ahash_state_t *state, uint8_t *out);
+
+/**
+ * Frees a state. Passing null does nothing.
+ *
+ * # Safety
+ * `state` must be null or a valid state which is not used afterwards.
+ */
+void ahash_state_free(ahash_state_t *state);
+
+/**
+ * Creates a new hasher using the keys of `state`. (`RandomState::build_hasher`)
+ *
+ * # Safety
+ * `state` must be a valid state.
+ */
+ahash_hasher_t *ahash_hasher_new(const ahash_state_t *state);
+
+/**
+ * Creates a copy of `hasher` which continues from the same point.
+ *
+ * # Safety
+ * `hasher` must be a valid hasher.
+ */
+ahash_hasher_t *ahash_hasher_clone(const ahash_hasher_t *hasher);
+
+/**
+ * Adds `len` bytes from `buf` to the hasher. (`Hasher::write`)
+ *
+ * Like `Hasher::write` the result depends on how the data is divided between calls, not only on the data.
+ *
+ * # Safety
+ * `hasher` must be a valid hasher and `buf` must point to `len` readable bytes. (It may be null if `len` is 0)
+ */
+void ahash_hasher_write(ahash_hasher_t *hasher,
+                        const uint8_t *buf,
+                        size_t len);
+
+/**
+ * Adds a `u64` to the hasher. (`Hasher::write_u64`)
+ *
+ * # Safety
+ * `hasher` must be a valid hasher.
+ */
+void ahash_hasher_write_u64(ahash_hasher_t *hasher, uint64_t value);
+
+/**
+ * Returns the hash of the data written so far. The hasher can continue to be used afterwards. (`Hasher::finish`)
+ *
+ * # Safety
+ * `hasher` must be a valid hasher.
+ */
+uint64_t ahash_hasher_finish(const ahash_hasher_t *hasher);
+
+/**
+ * Frees a hasher. Passing null does nothing.
+ *
+ * # Safety
+ * `hasher` must be null or a valid hasher which is not used afterwards.
+ */
+void ahash_hasher_free(ahash_hasher_t *hasher);
+
+/**
+ * Hashes `len` bytes from `buf` with a single call to `write`.
+ * This is the same as `ahash_hasher_new` followed by `ahash_hasher_write` and `ahash_hasher_finish`.
+ *
+ * (This is not the same as hashing a `[u8]` in Rust via `Hash`, which also hashes the length)
+ *
+ * # Safety
+ * `state` must be a valid state and `buf` must point to `len` readable bytes. (It may be null if `len` is 0)
+ */
+uint64_t ahash_hash_bytes(const ahash_state_t *state,
+                          const uint8_t *buf,
+                          size_t len);
+
+/**
+ * Hashes a `u64`. This is the same as `ahash_hasher_new` followed by `ahash_hasher_write_u64` and
+ * `ahash_hasher_finish`, and the same as hashing a `u64` in Rust via `Hash`.
+ *
+ * # Safety
+ * `state` must be a valid state.
+ */
+uint64_t ahash_hash_u64(const ahash_state_t *state, uint64_t value);
+
+/**
+ * The entry point used by SMHasher. (See `0001-Add-support-for-aHash.patch`)
+ *
+ * This creates a new state for each call, deriving its seeds from `seed`. Other callers should create a state once
+ * with one of the `ahash_state_*` functions instead.
+ *
+ * # Safety
+ * `buf` must point to `len` readable bytes. (It may be null if `len` is 0)
+ */
+uint64_t ahash64(const void *buf,
+                 size_t len,
+                 uint64_t seed);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* AHASH_H */
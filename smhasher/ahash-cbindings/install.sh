@@ -1 +1 @@
-cargo build --release && sudo cp target/release/libahash_c.a /usr/local/lib/
\ No newline at end of file
+cargo build --release && sudo cp target/release/libahash_c.a /usr/local/lib/ && sudo cp include/ahash.h /usr/local/include/
@@ -1,12 +1,190 @@
+//! C bindings for aHash.
+//!
+//! The declarations are in `include/ahash.h`. States and hashers are opaque and heap allocated: each `*_new`,
+//! `*_clone` or `*_from_*` function returns a pointer which must be released with the matching `*_free` function.
+//!
+//! A hash computed through these functions is the same as the one computed in Rust by the equivalent calls on a
+//! `RandomState` with the same keys and the `AHasher` it builds, provided both are compiled for the same target
+//! features. (See README.md)
+#![allow(non_camel_case_types)]
+
+use ahash::{AHasher, RandomState};
 use core::slice;
-use ahash::*;
-use std::hash::{Hasher, BuildHasher};
+use std::hash::{BuildHasher, Hasher};
+
+/// The keys used to create hashers. (A `RandomState`)
+pub struct ahash_state_t {
+    state: RandomState,
+}
+
+/// A hasher which is in the process of hashing some data. (An `AHasher`)
+#[derive(Clone)]
+pub struct ahash_hasher_t {
+    hasher: AHasher,
+}
+
+fn into_raw_state(state: RandomState) -> *mut ahash_state_t {
+    Box::into_raw(Box::new(ahash_state_t { state }))
+}
 
+fn into_raw_hasher(hasher: AHasher) -> *mut ahash_hasher_t {
+    Box::into_raw(Box::new(ahash_hasher_t { hasher }))
+}
+
+/// Allows a null pointer to be passed for an empty buffer.
+unsafe fn as_slice<'a>(buf: *const u8, len: usize) -> &'a [u8] {
+    if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(buf, len)
+    }
+}
+
+/// Creates a state from four keys. (`RandomState::with_keys`)
 #[no_mangle]
-pub extern "C" fn ahash64(buf: *const (), len: usize, seed: u64) -> u64 {
-    let buf: &[u8] = unsafe { slice::from_raw_parts(buf as *const u8, len) };
-    let mut hasher = RandomState::with_seeds(std::f64::consts::PI as u64 ^ seed, std::f64::consts::E as u64 ^ seed).build_hasher();
-    hasher.write(buf);
+pub extern "C" fn ahash_state_new(k0: u64, k1: u64, k2: u64, k3: u64) -> *mut ahash_state_t {
+    into_raw_state(RandomState::with_keys(k0, k1, k2, k3))
+}
+
+/// Creates a state from two seeds. (`RandomState::with_seeds`)
+#[no_mangle]
+pub extern "C" fn ahash_state_from_seeds(k0: u64, k1: u64) -> *mut ahash_state_t {
+    into_raw_state(RandomState::with_seeds(k0, k1))
+}
+
+/// Creates a state with random keys. (`RandomState::new`)
+#[no_mangle]
+pub extern "C" fn ahash_state_new_random() -> *mut ahash_state_t {
+    into_raw_state(RandomState::new())
+}
+
+/// Creates a state from the 32 bytes produced by `ahash_state_to_bytes` or `RandomState::to_bytes`.
+///
+/// # Safety
+/// `bytes` must point to 32 readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ahash_state_from_bytes(bytes: *const u8) -> *mut ahash_state_t {
+    let mut array = [0; 32];
+    array.copy_from_slice(slice::from_raw_parts(bytes, 32));
+    into_raw_state(RandomState::from_bytes(array))
+}
+
+/// Writes the keys of `state` as 32 bytes to `out`. (`RandomState::to_bytes`)
+///
+/// # Safety
+/// `state` must be a valid state and `out` must point to 32 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ahash_state_to_bytes(state: *const ahash_state_t, out: *mut u8) {
+    slice::from_raw_parts_mut(out, 32).copy_from_slice(&(*state).state.to_bytes());
+}
+
+/// Frees a state. Passing null does nothing.
+///
+/// # Safety
+/// `state` must be null or a valid state which is not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn ahash_state_free(state: *mut ahash_state_t) {
+    if !state.is_null() {
+        drop(Box::from_raw(state));
+    }
+}
+
+/// Creates a new hasher using the keys of `state`. (`RandomState::build_hasher`)
+///
+/// # Safety
+/// `state` must be a valid state.
+#[no_mangle]
+pub unsafe extern "C" fn ahash_hasher_new(state: *const ahash_state_t) -> *mut ahash_hasher_t {
+    into_raw_hasher((*state).state.build_hasher())
+}
+
+/// Creates a copy of `hasher` which continues from the same point.
+///
+/// # Safety
+/// `hasher` must be a valid hasher.
+#[no_mangle]
+pub unsafe extern "C" fn ahash_hasher_clone(hasher: *const ahash_hasher_t) -> *mut ahash_hasher_t {
+    Box::into_raw(Box::new((*hasher).clone()))
+}
+
+/// Adds `len` bytes from `buf` to the hasher. (`Hasher::write`)
+///
+/// Like `Hasher::write` the result depends on how the data is divided between calls, not only on the data.
+///
+/// # Safety
+/// `hasher` must be a valid hasher and `buf` must point to `len` readable bytes. (It may be null if `len` is 0)
+#[no_mangle]
+pub unsafe extern "C" fn ahash_hasher_write(hasher: *mut ahash_hasher_t, buf: *const u8, len: usize) {
+    (*hasher).hasher.write(as_slice(buf, len));
+}
+
+/// Adds a `u64` to the hasher. (`Hasher::write_u64`)
+///
+/// # Safety
+/// `hasher` must be a valid hasher.
+#[no_mangle]
+pub unsafe extern "C" fn ahash_hasher_write_u64(hasher: *mut ahash_hasher_t, value: u64) {
+    (*hasher).hasher.write_u64(value);
+}
+
+/// Returns the hash of the data written so far. The hasher can continue to be used afterwards. (`Hasher::finish`)
+///
+/// # Safety
+/// `hasher` must be a valid hasher.
+#[no_mangle]
+pub unsafe extern "C" fn ahash_hasher_finish(hasher: *const ahash_hasher_t) -> u64 {
+    (*hasher).hasher.finish()
+}
+
+/// Frees a hasher. Passing null does nothing.
+///
+/// # Safety
+/// `hasher` must be null or a valid hasher which is not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn ahash_hasher_free(hasher: *mut ahash_hasher_t) {
+    if !hasher.is_null() {
+        drop(Box::from_raw(hasher));
+    }
+}
+
+/// Hashes `len` bytes from `buf` with a single call to `write`.
+/// This is the same as `ahash_hasher_new` followed by `ahash_hasher_write` and `ahash_hasher_finish`.
+///
+/// (This is not the same as hashing a `[u8]` in Rust via `Hash`, which also hashes the length)
+///
+/// # Safety
+/// `state` must be a valid state and `buf` must point to `len` readable bytes. (It may be null if `len` is 0)
+#[no_mangle]
+pub unsafe extern "C" fn ahash_hash_bytes(state: *const ahash_state_t, buf: *const u8, len: usize) -> u64 {
+    let mut hasher = (*state).state.build_hasher();
+    hasher.write(as_slice(buf, len));
+    hasher.finish()
+}
+
+/// Hashes a `u64`. This is the same as `ahash_hasher_new` followed by `ahash_hasher_write_u64` and
+/// `ahash_hasher_finish`, and the same as hashing a `u64` in Rust via `Hash`.
+///
+/// # Safety
+/// `state` must be a valid state.
+#[no_mangle]
+pub unsafe extern "C" fn ahash_hash_u64(state: *const ahash_state_t, value: u64) -> u64 {
+    let mut hasher = (*state).state.build_hasher();
+    hasher.write_u64(value);
     hasher.finish()
 }
 
+/// The entry point used by SMHasher. (See `0001-Add-support-for-aHash.patch`)
+///
+/// This creates a new state for each call, deriving its seeds from `seed`. Other callers should create a state once
+/// with one of the `ahash_state_*` functions instead.
+///
+/// # Safety
+/// `buf` must point to `len` readable bytes. (It may be null if `len` is 0)
+#[no_mangle]
+pub unsafe extern "C" fn ahash64(buf: *const (), len: usize, seed: u64) -> u64 {
+    let buf = as_slice(buf as *const u8, len);
+    let mut hasher = RandomState::with_seeds(std::f64::consts::PI as u64 ^ seed, std::f64::consts::E as u64 ^ seed)
+        .build_hasher();
+    hasher.write(buf);
+    hasher.finish()
+}
@@ -0,0 +1,104 @@
+/*
+ * Exercises the C API. Checks that the different ways of computing a hash agree, and prints hashes computed with
+ * known keys so that tests/c_api.rs can compare them with the ones computed in Rust.
+ */
+#include "ahash.h"
+
+#include <inttypes.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+static int failures = 0;
+
+#define CHECK(condition)                                                       \
+    do {                                                                       \
+        if (!(condition)) {                                                    \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #condition); \
+            failures++;                                                        \
+        }                                                                      \
+    } while (0)
+
+static void print_hash(const char *name, uint64_t hash) {
+    printf("%s %" PRIu64 "\n", name, hash);
+}
+
+int main(void) {
+    const uint8_t *text = (const uint8_t *)"Hello, world!";
+    size_t text_len = strlen((const char *)text);
+    uint8_t data[100];
+    for (size_t i = 0; i < sizeof(data); i++) {
+        data[i] = (uint8_t)i;
+    }
+
+    ahash_state_t *state = ahash_state_new(1, 2, 3, 4);
+    CHECK(state != NULL);
+
+    /* One-shot helpers */
+    print_hash("bytes_text", ahash_hash_bytes(state, text, text_len));
+    print_hash("bytes_empty", ahash_hash_bytes(state, NULL, 0));
+    print_hash("bytes_data", ahash_hash_bytes(state, data, sizeof(data)));
+    print_hash("u64_0", ahash_hash_u64(state, 0));
+    print_hash("u64_max", ahash_hash_u64(state, UINT64_MAX));
+
+    /* Streaming matches one-shot */
+    ahash_hasher_t *hasher = ahash_hasher_new(state);
+    ahash_hasher_write(hasher, text, text_len);
+    CHECK(ahash_hasher_finish(hasher) == ahash_hash_bytes(state, text, text_len));
+    /* finish does not consume the hasher */
+    CHECK(ahash_hasher_finish(hasher) == ahash_hash_bytes(state, text, text_len));
+    ahash_hasher_free(hasher);
+
+    hasher = ahash_hasher_new(state);
+    ahash_hasher_write_u64(hasher, 12345);
+    CHECK(ahash_hasher_finish(hasher) == ahash_hash_u64(state, 12345));
+    ahash_hasher_free(hasher);
+
+    /* Several writes */
+    hasher = ahash_hasher_new(state);
+    ahash_hasher_write(hasher, text, text_len);
+    ahash_hasher_write_u64(hasher, 42);
+    ahash_hasher_write(hasher, data, sizeof(data));
+    print_hash("stream", ahash_hasher_finish(hasher));
+
+    /* A clone continues from the same point, independently of the original */
+    ahash_hasher_t *clone = ahash_hasher_clone(hasher);
+    CHECK(ahash_hasher_finish(clone) == ahash_hasher_finish(hasher));
+    ahash_hasher_write_u64(clone, 7);
+    CHECK(ahash_hasher_finish(clone) != ahash_hasher_finish(hasher));
+    ahash_hasher_write_u64(hasher, 7);
+    CHECK(ahash_hasher_finish(clone) == ahash_hasher_finish(hasher));
+    ahash_hasher_free(clone);
+    ahash_hasher_free(hasher);
+
+    /* Serialized states hash the same */
+    uint8_t bytes[32];
+    ahash_state_to_bytes(state, bytes);
+    ahash_state_t *copy = ahash_state_from_bytes(bytes);
+    CHECK(ahash_hash_bytes(copy, text, text_len) == ahash_hash_bytes(state, text, text_len));
+    ahash_state_free(copy);
+
+    /* Different keys give different hashes */
+    ahash_state_t *seeded = ahash_state_from_seeds(1, 2);
+    print_hash("seeds_text", ahash_hash_bytes(seeded, text, text_len));
+    CHECK(ahash_hash_bytes(seeded, text, text_len) != ahash_hash_bytes(state, text, text_len));
+    ahash_state_free(seeded);
+
+    ahash_state_t *random1 = ahash_state_new_random();
+    ahash_state_t *random2 = ahash_state_new_random();
+    CHECK(ahash_hash_bytes(random1, text, text_len) != ahash_hash_bytes(random2, text, text_len));
+    ahash_state_free(random1);
+    ahash_state_free(random2);
+
+    print_hash("ahash64", ahash64(text, text_len, 99));
+
+    ahash_state_free(state);
+    ahash_state_free(NULL);
+    ahash_hasher_free(NULL);
+
+    if (failures != 0) {
+        fprintf(stderr, "%d checks failed\n", failures);
+        return EXIT_FAILURE;
+    }
+    return EXIT_SUCCESS;
+}
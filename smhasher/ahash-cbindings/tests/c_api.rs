@@ -0,0 +1,99 @@
+//! Compiles tests/c/test_ahash.c against include/ahash.h and the static library, runs it, and checks that the hashes
+//! it prints are the same as those computed in Rust.
+use ahash::RandomState;
+use std::collections::HashMap;
+use std::env;
+use std::hash::{BuildHasher, Hasher};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The directory the library is built in. (The test executable is in its `deps` subdirectory)
+fn target_dir() -> PathBuf {
+    let exe = env::current_exe().unwrap();
+    exe.parent().unwrap().parent().unwrap().to_path_buf()
+}
+
+fn compile_and_run() -> HashMap<String, u64> {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let target_dir = target_dir();
+    // The static library is not built as part of building the tests.
+    let mut cargo = Command::new(env!("CARGO"));
+    cargo
+        .args(["build", "--lib", "--manifest-path"])
+        .arg(manifest_dir.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(target_dir.parent().unwrap());
+    if target_dir.ends_with("release") {
+        cargo.arg("--release");
+    }
+    let status = cargo.status().unwrap();
+    assert!(status.success(), "Building the library failed");
+    let library = target_dir.join("libahash_c.a");
+    let executable = target_dir.join("test_ahash_c");
+
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(compiler)
+        .arg("-std=c99")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg(manifest_dir.join("tests/c/test_ahash.c"))
+        .arg(&library)
+        .args(["-lpthread", "-ldl", "-lm"])
+        .arg("-o")
+        .arg(&executable)
+        .status()
+        .expect("Failed to run the C compiler");
+    assert!(status.success(), "Compiling the C test failed");
+
+    let output = Command::new(&executable).output().unwrap();
+    assert!(
+        output.status.success(),
+        "The C test failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let mut parts = line.split(' ');
+            let name = parts.next().unwrap().to_string();
+            (name, parts.next().unwrap().parse().unwrap())
+        })
+        .collect()
+}
+
+fn hash_bytes(state: &RandomState, bytes: &[u8]) -> u64 {
+    let mut hasher = state.build_hasher();
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+#[cfg(unix)]
+#[test]
+fn test_c_hashes_match_rust() {
+    let hashes = compile_and_run();
+    let state = RandomState::with_keys(1, 2, 3, 4);
+    let text = b"Hello, world!";
+    let data: Vec<u8> = (0..100).collect();
+
+    let mut expected = HashMap::new();
+    expected.insert("bytes_text", hash_bytes(&state, text));
+    expected.insert("bytes_empty", hash_bytes(&state, &[]));
+    expected.insert("bytes_data", hash_bytes(&state, &data));
+    expected.insert("u64_0", state.hash_one(0_u64));
+    expected.insert("u64_max", state.hash_one(u64::MAX));
+    let mut hasher = state.build_hasher();
+    hasher.write(text);
+    hasher.write_u64(42);
+    hasher.write(&data);
+    expected.insert("stream", hasher.finish());
+    expected.insert("seeds_text", hash_bytes(&RandomState::with_seeds(1, 2), text));
+    expected.insert("ahash64", unsafe { ahash_c::ahash64(text.as_ptr() as *const (), text.len(), 99) });
+
+    assert_eq!(expected.len(), hashes.len(), "{:?}", hashes);
+    for (name, value) in expected {
+        assert_eq!(Some(&value), hashes.get(name), "{}", name);
+    }
+}
@@ -0,0 +1,37 @@
+//! Checks that include/ahash.h is what cbindgen generates from src/lib.rs, so the header can't drift from the
+//! functions it declares.
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn test_header_is_up_to_date() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let output = match Command::new("cbindgen")
+        .arg("--config")
+        .arg(manifest_dir.join("cbindgen.toml"))
+        .arg(manifest_dir)
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            eprintln!("Skipping the header check: cbindgen is not installed (`cargo install cbindgen`)");
+            return;
+        }
+        Err(e) => panic!("Failed to run cbindgen: {}", e),
+    };
+    assert!(
+        output.status.success(),
+        "cbindgen failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let generated = String::from_utf8(output.stdout).unwrap();
+    let checked_in = fs::read_to_string(manifest_dir.join("include/ahash.h")).unwrap();
+    assert!(
+        generated == checked_in,
+        "include/ahash.h is out of date. Regenerate it with:\n    cbindgen --config cbindgen.toml --output include/ahash.h\n\
+         cbindgen generated:\n{}",
+        generated
+    );
+}
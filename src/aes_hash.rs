@@ -1,9 +1,14 @@
 use crate::convert::*;
 use crate::operations::*;
+use crate::stream::BlockHasher;
 #[cfg(feature = "specialize")]
 use crate::HasherExt;
 use core::hash::Hasher;
 
+/// The number of keys hashed at the same time by `hash_u64_batch`. (Enough to cover the latency of an AES round on
+/// current CPUs)
+const LANES: usize = 8;
+
 /// A `Hasher` for hashing an arbitrary stream of bytes.
 ///
 /// Instances of [`AHasher`] represent state that is updated while hashing data.
@@ -67,6 +72,69 @@ impl AHasher {
         }
     }
 
+    /// Returns the full 128 bit hash of the data written so far.
+    ///
+    /// `finish()` only returns the lower 64 bits of this value. When hashes are used as identifiers for a very
+    /// large number of items (rather than as keys in a `HashMap`) the additional bits make collisions much less likely.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::hash::Hasher;
+    /// use ahash::AHasher;
+    ///
+    /// let mut hasher = AHasher::new_with_keys(1234, 5678);
+    /// hasher.write(b"Some data");
+    ///
+    /// assert_eq!(hasher.finish(), hasher.finish_u128() as u64);
+    /// ```
+    #[inline]
+    pub fn finish_u128(&self) -> u128 {
+        let combined = aesdec(self.sum, self.enc);
+        aesenc(aesenc(combined, self.key), combined)
+    }
+
+    /// Hashes each of the `keys` as if by `write_u64` followed by `finish` on a clone of this hasher.
+    ///
+    /// The keys are processed `LANES` at a time, with each AES round applied to every lane before the next round is
+    /// started. As the rounds of different keys are independent, the CPU can issue them back to back rather than
+    /// waiting for the latency of each one.
+    #[inline]
+    #[allow(dead_code)] // Is not called if the fallback hash is used.
+    pub(crate) fn hash_u64_batch(&self, keys: &[u64], out: &mut [u64]) {
+        let mut key_chunks = keys.chunks_exact(LANES);
+        let mut out_chunks = out.chunks_exact_mut(LANES);
+        for (keys, out) in (&mut key_chunks).zip(&mut out_chunks) {
+            let mut enc = [0; LANES];
+            let mut combined = [0; LANES];
+            for (enc, key) in enc.iter_mut().zip(keys) {
+                *enc = aesenc(self.enc, *key as u128);
+            }
+            for ((combined, enc), key) in combined.iter_mut().zip(enc.iter()).zip(keys) {
+                *combined = aesdec(shuffle_and_add(self.sum, *key as u128), *enc);
+            }
+            for (enc, combined) in enc.iter_mut().zip(combined.iter()) {
+                *enc = aesenc(*combined, self.key);
+            }
+            for ((out, enc), combined) in out.iter_mut().zip(enc.iter()).zip(combined.iter()) {
+                let result: [u64; 2] = aesenc(*enc, *combined).convert();
+                *out = result[0];
+            }
+        }
+        for (key, out) in key_chunks.remainder().iter().zip(out_chunks.into_remainder()) {
+            let mut hasher = self.clone();
+            hasher.write_u64(*key);
+            *out = hasher.finish();
+        }
+    }
+
+    /// Hashes each of the `keys` as if by `write` followed by `finish` on a clone of this hasher.
+    #[inline]
+    #[allow(dead_code)] // Is not called if the fallback hash is used.
+    pub(crate) fn hash_slice_batch(&self, keys: &[&[u8]], out: &mut [u64]) {
+        crate::batch::hash_slices(self, keys, out)
+    }
+
     #[inline(always)]
     fn add_in_length(&mut self, length: u64) {
         //This will be scrambled by the next AES round.
@@ -90,21 +158,101 @@ impl AHasher {
     }
 }
 
+/// The state of the loop used to hash inputs longer than 64 bytes. Each 64 byte block is split between 4 independent
+/// lanes which are combined at the end.
+#[derive(Debug, Clone)]
+pub(crate) struct Blocks {
+    current: [u128; 4],
+    sum: [u128; 2],
+}
+
+impl Blocks {
+    #[inline(always)]
+    fn new(key: u128) -> Self {
+        Blocks {
+            current: [key; 4],
+            sum: [key, key],
+        }
+    }
+
+    /// Starts the loop using the last 64 bytes of the input. (These overlap with the last block processed)
+    #[inline(always)]
+    fn from_tail(key: u128, tail: [u128; 4]) -> Self {
+        let mut current: [u128; 4] = [key; 4];
+        current[0] = aesenc(current[0], tail[0]);
+        current[1] = aesenc(current[1], tail[1]);
+        current[2] = aesenc(current[2], tail[2]);
+        current[3] = aesenc(current[3], tail[3]);
+        let mut sum: [u128; 2] = [key, key];
+        sum[0] = add_by_64s(sum[0].convert(), tail[0].convert()).convert();
+        sum[1] = add_by_64s(sum[1].convert(), tail[1].convert()).convert();
+        sum[0] = shuffle_and_add(sum[0], tail[2]);
+        sum[1] = shuffle_and_add(sum[1], tail[3]);
+        Blocks { current, sum }
+    }
+
+    #[inline(always)]
+    fn update(&mut self, blocks: [u128; 4]) {
+        self.current[0] = aesenc(self.current[0], blocks[0]);
+        self.current[1] = aesenc(self.current[1], blocks[1]);
+        self.current[2] = aesenc(self.current[2], blocks[2]);
+        self.current[3] = aesenc(self.current[3], blocks[3]);
+        self.sum[0] = shuffle_and_add(self.sum[0], blocks[0]);
+        self.sum[1] = shuffle_and_add(self.sum[1], blocks[1]);
+        self.sum[0] = shuffle_and_add(self.sum[0], blocks[2]);
+        self.sum[1] = shuffle_and_add(self.sum[1], blocks[3]);
+    }
+
+    #[inline(always)]
+    fn hash_into(self, hasher: &mut AHasher) {
+        hasher.hash_in_2(
+            aesenc(self.current[0], self.current[1]),
+            aesenc(self.current[2], self.current[3]),
+        );
+        hasher.hash_in(add_by_64s(self.sum[0].convert(), self.sum[1].convert()).convert());
+    }
+}
+
+impl BlockHasher for AHasher {
+    type Blocks = Blocks;
+
+    #[inline]
+    fn start_blocks(&self) -> Blocks {
+        Blocks::new(self.key)
+    }
+
+    #[inline]
+    fn update_blocks(&self, blocks: &mut Blocks, mut data: &[u8]) {
+        while !data.is_empty() {
+            let (block, rest) = data.read_u128x4();
+            blocks.update(block);
+            data = rest;
+        }
+    }
+
+    #[inline]
+    fn finish_blocks(&self, mut blocks: Blocks, tail: &[u8; 64], length: u64) -> AHasher {
+        let mut hasher = self.clone();
+        hasher.add_in_length(length);
+        blocks.update((*tail).convert());
+        blocks.hash_into(&mut hasher);
+        hasher
+    }
+}
+
 #[cfg(feature = "specialize")]
 impl HasherExt for AHasher {
     #[inline]
     fn hash_u64(self, value: u64) -> u64 {
         let mask = self.sum as u64;
         let rot = (self.enc & 64) as u32;
-        (value ^ mask)
-            .folded_multiply(crate::random_state::MULTIPLE)
-            .rotate_left(rot)
+        folded_multiply(value ^ mask, crate::random_state::MULTIPLE).rotate_left(rot)
     }
 
     #[inline]
     fn short_finish(&self) -> u64 {
         let buffer: [u64; 2] = self.enc.convert();
-        buffer[0].folded_multiply(buffer[1])
+        folded_multiply(buffer[0], buffer[1])
     }
 }
 
@@ -157,7 +305,7 @@ impl Hasher for AHasher {
                     [data.read_u16().0 as u64, data[data.len() - 1] as u64]
                 }
             } else {
-                if data.len() > 0 {
+                if !data.is_empty() {
                     [data[0] as u64, 0]
                 } else {
                     [0, 0]
@@ -168,30 +316,13 @@ impl Hasher for AHasher {
             if data.len() > 32 {
                 if data.len() > 64 {
                     let tail = data.read_last_u128x4();
-                    let mut current: [u128; 4] = [self.key; 4];
-                    current[0] = aesenc(current[0], tail[0]);
-                    current[1] = aesenc(current[1], tail[1]);
-                    current[2] = aesenc(current[2], tail[2]);
-                    current[3] = aesenc(current[3], tail[3]);
-                    let mut sum: [u128; 2] = [self.key, self.key];
-                    sum[0] = add_by_64s(sum[0].convert(), tail[0].convert()).convert();
-                    sum[1] = add_by_64s(sum[1].convert(), tail[1].convert()).convert();
-                    sum[0] = shuffle_and_add(sum[0], tail[2]);
-                    sum[1] = shuffle_and_add(sum[1], tail[3]);
+                    let mut blocks = Blocks::from_tail(self.key, tail);
                     while data.len() > 64 {
-                        let (blocks, rest) = data.read_u128x4();
-                        current[0] = aesenc(current[0], blocks[0]);
-                        current[1] = aesenc(current[1], blocks[1]);
-                        current[2] = aesenc(current[2], blocks[2]);
-                        current[3] = aesenc(current[3], blocks[3]);
-                        sum[0] = shuffle_and_add(sum[0], blocks[0]);
-                        sum[1] = shuffle_and_add(sum[1], blocks[1]);
-                        sum[0] = shuffle_and_add(sum[0], blocks[2]);
-                        sum[1] = shuffle_and_add(sum[1], blocks[3]);
+                        let (block, rest) = data.read_u128x4();
+                        blocks.update(block);
                         data = rest;
                     }
-                    self.hash_in_2(aesenc(current[0], current[1]), aesenc(current[2], current[3]));
-                    self.hash_in(add_by_64s(sum[0].convert(), sum[1].convert()).convert());
+                    blocks.hash_into(self);
                 } else {
                     //len 33-64
                     let (head, _) = data.read_u128x2();
@@ -213,17 +344,17 @@ impl Hasher for AHasher {
     }
     #[inline]
     fn finish(&self) -> u64 {
-        let combined = aesdec(self.sum, self.enc);
-        let result: [u64; 2] = aesenc(aesenc(combined, self.key), combined).convert();
+        let result: [u64; 2] = self.finish_u128().convert();
         result[0]
     }
 }
 
 #[cfg(test)]
 mod tests {
+    #[allow(unused_imports)] // Only used if the AES hasher is selected at compile time.
     use super::*;
     use crate::convert::Convert;
-    use crate::operations::aesenc;
+    use crate::operations::{aes_unavailable, aesenc};
     use crate::RandomState;
     use std::hash::{BuildHasher, Hasher};
     #[test]
@@ -236,7 +367,7 @@ mod tests {
         assert_ne!(h1, h2);
     }
 
-    #[cfg(feature = "compile-time-rng")]
+    #[cfg(all(feature = "compile-time-rng", target_feature = "aes"))]
     #[test]
     fn test_builder() {
         use std::collections::HashMap;
@@ -246,7 +377,7 @@ mod tests {
         map.insert(1, 3);
     }
 
-    #[cfg(feature = "compile-time-rng")]
+    #[cfg(all(feature = "compile-time-rng", target_feature = "aes"))]
     #[test]
     fn test_default() {
         let hasher_a = AHasher::default();
@@ -271,6 +402,9 @@ mod tests {
 
     #[test]
     fn test_hash() {
+        if aes_unavailable() {
+            return;
+        }
         let mut result: [u64; 2] = [0x6c62272e07bb0142, 0x62b821756295c58d];
         let value: [u64; 2] = [1 << 32, 0xFEDCBA9876543210];
         result = aesenc(value.convert(), result.convert()).convert();
@@ -284,6 +418,128 @@ mod tests {
         assert_ne!(hex::encode(result), hex::encode(result2));
     }
 
+    fn golden_hashes() -> Vec<u128> {
+        let input: Vec<u8> = (0..100_u8).map(|i| i.wrapping_mul(37).wrapping_add(11)).collect();
+        let mut hashes = Vec::new();
+        for &len in [0, 1, 2, 3, 4, 7, 8, 9, 15, 16, 17, 31, 32, 33, 63, 64, 65, 100].iter() {
+            let mut hasher = AHasher::new_with_keys(0x0123_4567_89AB_CDEF, 0xFEDC_BA98_7654_3210 << 64);
+            hasher.write(&input[..len]);
+            hashes.push(hasher.finish_u128());
+        }
+        let mut hasher = AHasher::new_with_keys(1234, 5678);
+        hasher.write_u64(0x0011_2233_4455_6677);
+        hashes.push(hasher.finish_u128());
+        hasher.write_u128(0x8899_AABB_CCDD_EEFF_0011_2233_4455_6677);
+        hashes.push(hasher.finish_u128());
+        hashes
+    }
+
+    /// These values were produced using AES-NI and SSSE3. The software implementation of AES must produce identical
+    /// results. (If the algorithm is changed, they should be regenerated on a machine which supports AES-NI)
+    #[test]
+    fn test_matches_hardware_aes() {
+        if aes_unavailable() {
+            return;
+        }
+        if cfg!(all(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            target_feature = "aes",
+            not(target_feature = "ssse3"),
+            not(miri)
+        )) {
+            // Byte swaps instead of the SSSE3 shuffle. (See `test_aes_without_ssse3_hashes_are_stable`)
+            return;
+        }
+        assert_eq!(golden_hashes(), GOLDEN.to_vec());
+    }
+
+    const GOLDEN: [u128; 20] = [
+        0x8849c708_2f0f1e5f_3eefe9ab_d577e1fa,
+        0x6e1d4b05_06b62a2b_418cb9a4_d69f87de,
+        0xa1ee8936_ae6759d3_10dda851_0298beca,
+        0xbd932805_6f430a46_8cdcd5e5_5f5a6fe5,
+        0x7980160f_f352e525_56b29171_cdcc5005,
+        0x32fad9ed_e9f07104_c490f9a3_32cf83cf,
+        0x8e7c8849_8553db35_79f38b54_170813f4,
+        0xbb596815_fce64953_9ff6b0cc_f450928e,
+        0x27f52152_65dba81f_e59f93ca_17443832,
+        0x7369ca96_97d0d4f7_c6451fd0_a7b9725e,
+        0x7896a9c4_3f96ae7e_031f4a52_0c94883c,
+        0x0ee84472_1e8c0f22_b6c0cac1_237abfe3,
+        0x0d27579d_2e4905a4_04c7a9a5_b7f9321b,
+        0xa068ebca_2deb2078_09eeb125_c8421f37,
+        0x58b7bbe6_6cbe4244_10c95625_f24a860d,
+        0x440b6d2a_e959ca47_95a26d47_aefc0134,
+        0x4fdbd9be_840a3ce6_49de97b3_0de4ed26,
+        0x76597630_c8bdae1e_57402ff9_514bf9dd,
+        0x2b85541d_b4ac139f_97a0c59b_b2465638,
+        0x6fc83069_0a6ef3f1_8a3634aa_8d32b9a9,
+    ];
+
+    #[test]
+    fn test_hash_u64_batch_matches_individual() {
+        if aes_unavailable() {
+            return;
+        }
+        let hasher = AHasher::test_with_keys(1, 2);
+        let keys: Vec<u64> = (0..(2 * LANES as u64 + 3)).map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15)).collect();
+        for len in 0..=keys.len() {
+            let mut out = vec![0; len];
+            hasher.hash_u64_batch(&keys[..len], &mut out);
+            for (key, hash) in keys.iter().zip(out.iter()) {
+                let mut individual = hasher.clone();
+                individual.write_u64(*key);
+                assert_eq!(individual.finish(), *hash);
+            }
+        }
+    }
+
+    /// The hashes of a build with AES-NI but not SSSE3 (`-C target-feature=+aes`, as recommended in the README), which
+    /// must not change. (Such builds shuffle with a byte swap)
+    #[cfg(all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "aes",
+        not(target_feature = "ssse3"),
+        not(miri)
+    ))]
+    #[test]
+    fn test_aes_without_ssse3_hashes_are_stable() {
+        const EXPECTED: [u64; 17] = [
+            0x8C2F_626F_FB2E_BCCA,
+            0x6CB1_58C6_897E_2858,
+            0xAF9E_E579_CDB5_683F,
+            0x20B4_F9EE_99E9_D023,
+            0x7BAD_CCA1_4FEA_F618,
+            0x5F4C_F262_595E_AE7B,
+            0xC065_8D15_C5AC_A020,
+            0x2F73_406D_0C5E_5C11,
+            0xFA14_6967_3C87_8340,
+            0xAB4D_6F2E_A96B_0D91,
+            0xA994_730A_2BB0_4E95,
+            0x82D5_DA9B_072B_616F,
+            0xC2DF_DB9B_AFC1_77EA,
+            0x699C_ED9E_0FA7_D89E,
+            0x8C2F_626F_FB2E_BCCA,
+            0xA92C_A005_4FDA_E92E,
+            0x95AA_11BF_9503_A37F,
+        ];
+        let key1 = 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210;
+        let key2 = 0x0f1e_2d3c_4b5a_6978_8796_a5b4_c3d2_e1f0;
+        let data: Vec<u8> = (0..200_u32).map(|i| (i * 7 + 3) as u8).collect();
+        let mut hashes = Vec::new();
+        for len in [0, 1, 3, 7, 8, 15, 16, 17, 32, 33, 64, 65, 128, 200].iter() {
+            let mut hasher = AHasher::new_with_keys(key1, key2);
+            hasher.write(&data[..*len]);
+            hashes.push(hasher.finish());
+        }
+        for value in [0, 1, u64::MAX].iter() {
+            let mut hasher = AHasher::new_with_keys(key1, key2);
+            hasher.write_u64(*value);
+            hashes.push(hasher.finish());
+        }
+        assert_eq!(&EXPECTED[..], &hashes[..]);
+    }
+
     #[test]
     fn test_conversion() {
         let input: &[u8] = "dddddddd".as_bytes();
@@ -0,0 +1,685 @@
+//! The `AHashMap` used when the `alloc` feature is enabled without `std`, and the types its methods return.
+
+use crate::table::{RawDrain, RawIntoIter, RawIter, RawIterMut, RawTable};
+use alloc::collections::TryReserveError;
+use core::borrow::Borrow;
+use core::fmt::{self, Debug};
+use core::hash::{BuildHasher, Hash};
+use core::iter::{FromIterator, FusedIterator};
+use core::mem;
+use core::ops::Index;
+
+/// A hash map using [`RandomState`](crate::RandomState) to hash the items.
+///
+/// This is used in place of the `std` based version when the `alloc` feature is enabled without the `std` feature.
+/// It is backed by an open addressing table implemented in this crate, and provides the same methods and trait
+/// implementations as the `std` version, except for `Deref` and conversions to and from `std::collections::HashMap`.
+/// Like `HashMap` the iteration order is unspecified.
+#[derive(Clone)]
+pub struct AHashMap<K, V, S = crate::RandomState> {
+    table: RawTable<(K, V)>,
+    hash_builder: S,
+}
+
+impl<K, V, S> AHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    pub fn new() -> Self {
+        AHashMap::with_hasher(S::default())
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        AHashMap::with_capacity_and_hasher(capacity, S::default())
+    }
+}
+
+impl<K, V, S> AHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    pub fn with_hasher(hash_builder: S) -> Self {
+        AHashMap {
+            table: RawTable::new(),
+            hash_builder,
+        }
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        AHashMap {
+            table: RawTable::with_capacity(capacity),
+            hash_builder,
+        }
+    }
+
+    #[inline]
+    fn find<Q>(&self, k: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table
+            .find(self.hash_builder.hash_one(k), |(key, _)| k == key.borrow())
+    }
+
+    /// Reserves capacity for at least `additional` more elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new allocation size overflows `usize`.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.table.reserve(additional)
+    }
+
+    /// Tries to reserve capacity for at least `additional` more elements, returning an error instead of panicking if
+    /// the allocation fails.
+    #[inline]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.table.try_reserve(additional)
+    }
+
+    /// Shrinks the capacity of the map as much as possible.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.table.shrink_to(0)
+    }
+
+    /// Shrinks the capacity of the map to the larger of `min_capacity` and what is needed for the current entries.
+    #[inline]
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.table.shrink_to(min_capacity)
+    }
+
+    /// Gets the given key's corresponding entry in the map for in-place manipulation.
+    #[inline]
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        let hash = self.hash_builder.hash_one(&key);
+        match self.table.find(hash, |(k, _)| *k == key) {
+            Some(index) => Entry::Occupied(OccupiedEntry {
+                table: &mut self.table,
+                index,
+            }),
+            None => Entry::Vacant(VacantEntry {
+                table: &mut self.table,
+                hash,
+                key,
+            }),
+        }
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_key_value(k).map(|(_, v)| v)
+    }
+
+    /// Returns the key-value pair corresponding to the supplied key.
+    #[inline]
+    pub fn get_key_value<Q>(&self, k: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(k).map(|index| {
+            let (key, value) = self.table.get(index);
+            (key, value)
+        })
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    #[inline]
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(k).is_some()
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find(k)?;
+        Some(&mut self.table.get_mut(index).1)
+    }
+
+    /// Inserts a key-value pair into the map.
+    ///
+    /// If the map did not have this key present, `None` is returned. Otherwise the value is updated and the old value
+    /// is returned. The key is not updated.
+    #[inline]
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        match self.entry(k) {
+            Entry::Occupied(mut entry) => Some(entry.insert(v)),
+            Entry::Vacant(entry) => {
+                entry.insert(v);
+                None
+            }
+        }
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key was previously in the map.
+    #[inline]
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove_entry(k).map(|(_, v)| v)
+    }
+
+    /// Removes a key from the map, returning the stored key and value if the key was previously in the map.
+    #[inline]
+    pub fn remove_entry<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find(k)?;
+        Some(self.table.remove(index))
+    }
+
+    /// Replaces the key equal to `key` with `key` itself and returns the old one, or returns `key` back if there is no
+    /// such key. (Used by `AHashSet::replace`)
+    pub(crate) fn replace_key(&mut self, key: K) -> Result<K, K> {
+        match self.find(&key) {
+            Some(index) => Ok(mem::replace(&mut self.table.get_mut(index).0, key)),
+            None => Err(key),
+        }
+    }
+}
+
+impl<K, V, S> AHashMap<K, V, S> {
+    /// Returns the number of elements the map can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.table.capacity()
+    }
+
+    /// An iterator visiting all keys in arbitrary order.
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys(self.table.iter())
+    }
+
+    /// Creates a consuming iterator visiting all the keys in arbitrary order.
+    #[inline]
+    pub fn into_keys(self) -> IntoKeys<K, V> {
+        IntoKeys(self.table.into_iter())
+    }
+
+    /// An iterator visiting all values in arbitrary order.
+    #[inline]
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values(self.table.iter())
+    }
+
+    /// An iterator visiting all values mutably in arbitrary order.
+    #[inline]
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut(self.table.iter_mut())
+    }
+
+    /// Creates a consuming iterator visiting all the values in arbitrary order.
+    #[inline]
+    pub fn into_values(self) -> IntoValues<K, V> {
+        IntoValues(self.table.into_iter())
+    }
+
+    /// An iterator visiting all key-value pairs in arbitrary order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter(self.table.iter())
+    }
+
+    /// An iterator visiting all key-value pairs in arbitrary order, with mutable references to the values.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut(self.table.iter_mut())
+    }
+
+    /// Returns the number of elements in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    /// Returns `true` if the map contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.table.len() == 0
+    }
+
+    /// Clears the map, returning all key-value pairs as an iterator. Keeps the allocated memory for reuse.
+    #[inline]
+    pub fn drain(&mut self) -> Drain<'_, K, V> {
+        Drain(self.table.drain())
+    }
+
+    /// Retains only the elements specified by the predicate.
+    #[inline]
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.table.retain(|(k, v)| f(k, v))
+    }
+
+    /// Clears the map, removing all key-value pairs. Keeps the allocated memory for reuse.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.table.clear()
+    }
+
+    /// Returns a reference to the map's [`BuildHasher`].
+    #[inline]
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+}
+
+impl<K, V, const N: usize> From<[(K, V); N]> for AHashMap<K, V>
+where
+    K: Eq + Hash,
+{
+    fn from(arr: [(K, V); N]) -> Self {
+        AHashMap::from_iter(arr)
+    }
+}
+
+impl<K, V, S> PartialEq for AHashMap<K, V, S>
+where
+    K: Eq + Hash,
+    V: PartialEq,
+    S: BuildHasher,
+{
+    fn eq(&self, other: &AHashMap<K, V, S>) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl<K, V, S> Eq for AHashMap<K, V, S>
+where
+    K: Eq + Hash,
+    V: Eq,
+    S: BuildHasher,
+{
+}
+
+impl<K, Q: ?Sized, V, S> Index<&Q> for AHashMap<K, V, S>
+where
+    K: Eq + Hash + Borrow<Q>,
+    Q: Eq + Hash,
+    S: BuildHasher,
+{
+    type Output = V;
+
+    /// Returns a reference to the value corresponding to the supplied key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not present in the `AHashMap`.
+    #[inline]
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("no entry found for key")
+    }
+}
+
+impl<K, V, S> Debug for AHashMap<K, V, S>
+where
+    K: Debug,
+    V: Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for AHashMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = AHashMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a AHashMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a mut AHashMap<K, V, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V, S> IntoIterator for AHashMap<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.table.into_iter())
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for AHashMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    #[inline]
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        let iter = iter.into_iter();
+        // If the map already has entries, some of the new keys may be duplicates, so only reserve half.
+        let reserve = if self.is_empty() {
+            iter.size_hint().0
+        } else {
+            iter.size_hint().0.div_ceil(2)
+        };
+        self.reserve(reserve);
+        iter.for_each(move |(k, v)| {
+            self.insert(k, v);
+        });
+    }
+}
+
+impl<'a, K, V, S> Extend<(&'a K, &'a V)> for AHashMap<K, V, S>
+where
+    K: Eq + Hash + Copy + 'a,
+    V: Copy + 'a,
+    S: BuildHasher,
+{
+    #[inline]
+    fn extend<T: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: T) {
+        self.extend(iter.into_iter().map(|(&k, &v)| (k, v)))
+    }
+}
+
+impl<K, V, S> Default for AHashMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    #[inline]
+    fn default() -> AHashMap<K, V, S> {
+        AHashMap::with_hasher(Default::default())
+    }
+}
+
+/// A view into a single entry in a map, which may either be vacant or occupied.
+///
+/// This is constructed from the [`entry`](AHashMap::entry) method on [`AHashMap`].
+pub enum Entry<'a, K, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+/// A view into an occupied entry in an [`AHashMap`]. It is part of the [`Entry`] enum.
+pub struct OccupiedEntry<'a, K, V> {
+    table: &'a mut RawTable<(K, V)>,
+    index: usize,
+}
+
+/// A view into a vacant entry in an [`AHashMap`]. It is part of the [`Entry`] enum.
+pub struct VacantEntry<'a, K, V> {
+    table: &'a mut RawTable<(K, V)>,
+    hash: u64,
+    key: K,
+}
+
+impl<'a, K, V> Entry<'a, K, V> {
+    /// Ensures a value is in the entry by inserting the default if empty, and returns a mutable reference to the value.
+    #[inline]
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Ensures a value is in the entry by inserting the result of `default` if empty, and returns a mutable reference
+    /// to the value.
+    #[inline]
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Ensures a value is in the entry by inserting the result of `default` (which is passed the key) if empty, and
+    /// returns a mutable reference to the value.
+    #[inline]
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let value = default(&entry.key);
+                entry.insert(value)
+            }
+        }
+    }
+
+    /// Returns a reference to this entry's key.
+    #[inline]
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Provides in-place mutable access to an occupied entry before any potential inserts into the map.
+    #[inline]
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        match self {
+            Entry::Occupied(mut entry) => {
+                f(entry.get_mut());
+                Entry::Occupied(entry)
+            }
+            Entry::Vacant(entry) => Entry::Vacant(entry),
+        }
+    }
+}
+
+impl<'a, K, V: Default> Entry<'a, K, V> {
+    /// Ensures a value is in the entry by inserting the default value if empty, and returns a mutable reference to the
+    /// value.
+    #[inline]
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    /// Gets a reference to the key in the entry.
+    #[inline]
+    pub fn key(&self) -> &K {
+        &self.table.get(self.index).0
+    }
+
+    /// Gets a reference to the value in the entry.
+    #[inline]
+    pub fn get(&self) -> &V {
+        &self.table.get(self.index).1
+    }
+
+    /// Gets a mutable reference to the value in the entry.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.table.get_mut(self.index).1
+    }
+
+    /// Converts the entry into a mutable reference to its value, with the lifetime of the map.
+    #[inline]
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.table.get_mut(self.index).1
+    }
+
+    /// Sets the value of the entry, and returns the entry's old value.
+    #[inline]
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// Takes the value out of the entry, and returns it.
+    #[inline]
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    /// Takes the ownership of the key and value from the map.
+    #[inline]
+    pub fn remove_entry(self) -> (K, V) {
+        self.table.remove(self.index)
+    }
+}
+
+impl<'a, K, V> VacantEntry<'a, K, V> {
+    /// Gets a reference to the key that would be used when inserting a value through the entry.
+    #[inline]
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Takes ownership of the key.
+    #[inline]
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Sets the value of the entry with its key, and returns a mutable reference to it.
+    #[inline]
+    pub fn insert(self, value: V) -> &'a mut V {
+        let index = self.table.insert(self.hash, (self.key, value));
+        &mut self.table.get_mut(index).1
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for Entry<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Entry::Occupied(entry) => f.debug_tuple("Entry").field(entry).finish(),
+            Entry::Vacant(entry) => f.debug_tuple("Entry").field(entry).finish(),
+        }
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for OccupiedEntry<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OccupiedEntry")
+            .field("key", self.key())
+            .field("value", self.get())
+            .finish()
+    }
+}
+
+impl<K: Debug, V> Debug for VacantEntry<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("VacantEntry").field(self.key()).finish()
+    }
+}
+
+/// Defines a wrapper around one of the table's iterators which maps each `(K, V)` to `$item`.
+macro_rules! map_iter {
+    ($(#[$doc:meta])* $name:ident $(<$lifetime:lifetime>)?, $raw:ty, $item:ty, |$entry:pat_param| $map:expr) => {
+        $(#[$doc])*
+        pub struct $name<$($lifetime,)? K, V>($raw);
+
+        impl<$($lifetime,)? K, V> Iterator for $name<$($lifetime,)? K, V> {
+            type Item = $item;
+
+            #[inline]
+            fn next(&mut self) -> Option<$item> {
+                self.0.next().map(|$entry| $map)
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.0.size_hint()
+            }
+        }
+
+        impl<$($lifetime,)? K, V> ExactSizeIterator for $name<$($lifetime,)? K, V> {}
+
+        impl<$($lifetime,)? K, V> FusedIterator for $name<$($lifetime,)? K, V> {}
+    };
+}
+
+map_iter!(
+    /// An iterator over the entries of an [`AHashMap`]. Created by [`AHashMap::iter`].
+    Iter<'a>, RawIter<'a, (K, V)>, (&'a K, &'a V), |(k, v)| (k, v)
+);
+map_iter!(
+    /// A mutable iterator over the entries of an [`AHashMap`]. Created by [`AHashMap::iter_mut`].
+    IterMut<'a>, RawIterMut<'a, (K, V)>, (&'a K, &'a mut V), |(k, v)| (&*k, v)
+);
+map_iter!(
+    /// An owning iterator over the entries of an [`AHashMap`]. Created by its `into_iter` method.
+    IntoIter, RawIntoIter<(K, V)>, (K, V), |entry| entry
+);
+map_iter!(
+    /// A draining iterator over the entries of an [`AHashMap`]. Created by [`AHashMap::drain`].
+    Drain<'a>, RawDrain<'a, (K, V)>, (K, V), |entry| entry
+);
+map_iter!(
+    /// An iterator over the keys of an [`AHashMap`]. Created by [`AHashMap::keys`].
+    Keys<'a>, RawIter<'a, (K, V)>, &'a K, |(k, _)| k
+);
+map_iter!(
+    /// An owning iterator over the keys of an [`AHashMap`]. Created by [`AHashMap::into_keys`].
+    IntoKeys, RawIntoIter<(K, V)>, K, |(k, _)| k
+);
+map_iter!(
+    /// An iterator over the values of an [`AHashMap`]. Created by [`AHashMap::values`].
+    Values<'a>, RawIter<'a, (K, V)>, &'a V, |(_, v)| v
+);
+map_iter!(
+    /// A mutable iterator over the values of an [`AHashMap`]. Created by [`AHashMap::values_mut`].
+    ValuesMut<'a>, RawIterMut<'a, (K, V)>, &'a mut V, |(_, v)| v
+);
+map_iter!(
+    /// An owning iterator over the values of an [`AHashMap`]. Created by [`AHashMap::into_values`].
+    IntoValues, RawIntoIter<(K, V)>, V, |(_, v)| v
+);
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Iter(self.0.clone())
+    }
+}
+
+impl<K, V> Clone for Keys<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Keys(self.0.clone())
+    }
+}
+
+impl<K, V> Clone for Values<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Values(self.0.clone())
+    }
+}
@@ -0,0 +1,580 @@
+//! The `AHashSet` used when the `alloc` feature is enabled without `std`, and the types its methods return.
+
+use crate::alloc_hash_map::{AHashMap, Drain as MapDrain, IntoKeys, Keys};
+use alloc::collections::TryReserveError;
+use core::borrow::Borrow;
+use core::fmt::{self, Debug};
+use core::hash::{BuildHasher, Hash};
+use core::iter::{Chain, FromIterator, FusedIterator};
+use core::ops::{BitAnd, BitOr, BitXor, Sub};
+
+/// A hash set using [`RandomState`](crate::RandomState) to hash the items.
+///
+/// This is used in place of the `std` based version when the `alloc` feature is enabled without the `std` feature.
+/// It is implemented as an [`AHashMap`] where the value is `()`, and provides the same methods and trait
+/// implementations as the `std` version, except for `Deref` and conversions to and from `std::collections::HashSet`.
+#[derive(Clone)]
+pub struct AHashSet<T, S = crate::RandomState>(AHashMap<T, (), S>);
+
+impl<T, S> AHashSet<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    pub fn new() -> Self {
+        AHashSet(AHashMap::new())
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        AHashSet(AHashMap::with_capacity(capacity))
+    }
+}
+
+impl<T, S> AHashSet<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    pub fn with_hasher(hash_builder: S) -> Self {
+        AHashSet(AHashMap::with_hasher(hash_builder))
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        AHashSet(AHashMap::with_capacity_and_hasher(capacity, hash_builder))
+    }
+
+    /// Reserves capacity for at least `additional` more elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new allocation size overflows `usize`.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional)
+    }
+
+    /// Tries to reserve capacity for at least `additional` more elements, returning an error instead of panicking if
+    /// the allocation fails.
+    #[inline]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.0.try_reserve(additional)
+    }
+
+    /// Shrinks the capacity of the set as much as possible.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit()
+    }
+
+    /// Shrinks the capacity of the set to the larger of `min_capacity` and what is needed for the current values.
+    #[inline]
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.0.shrink_to(min_capacity)
+    }
+
+    /// Visits the values representing the difference, i.e., the values that are in `self` but not in `other`.
+    #[inline]
+    pub fn difference<'a>(&'a self, other: &'a AHashSet<T, S>) -> Difference<'a, T, S> {
+        Difference {
+            iter: self.iter(),
+            other,
+        }
+    }
+
+    /// Visits the values representing the symmetric difference, i.e., the values that are in `self` or in `other`
+    /// but not in both.
+    #[inline]
+    pub fn symmetric_difference<'a>(&'a self, other: &'a AHashSet<T, S>) -> SymmetricDifference<'a, T, S> {
+        SymmetricDifference(self.difference(other).chain(other.difference(self)))
+    }
+
+    /// Visits the values representing the intersection, i.e., the values that are both in `self` and `other`.
+    #[inline]
+    pub fn intersection<'a>(&'a self, other: &'a AHashSet<T, S>) -> Intersection<'a, T, S> {
+        if self.len() <= other.len() {
+            Intersection {
+                iter: self.iter(),
+                other,
+            }
+        } else {
+            Intersection {
+                iter: other.iter(),
+                other: self,
+            }
+        }
+    }
+
+    /// Visits the values representing the union, i.e., all the values in `self` or `other`, without duplicates.
+    #[inline]
+    pub fn union<'a>(&'a self, other: &'a AHashSet<T, S>) -> Union<'a, T, S> {
+        if self.len() >= other.len() {
+            Union(self.iter().chain(other.difference(self)))
+        } else {
+            Union(other.iter().chain(self.difference(other)))
+        }
+    }
+
+    /// Returns `true` if the set contains a value.
+    #[inline]
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.contains_key(value)
+    }
+
+    /// Returns a reference to the value in the set, if any, that is equal to the given value.
+    #[inline]
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.get_key_value(value).map(|(k, _)| k)
+    }
+
+    /// Returns `true` if `self` has no elements in common with `other`.
+    #[inline]
+    pub fn is_disjoint(&self, other: &AHashSet<T, S>) -> bool {
+        self.intersection(other).next().is_none()
+    }
+
+    /// Returns `true` if the set is a subset of another, i.e., `other` contains at least all the values in `self`.
+    #[inline]
+    pub fn is_subset(&self, other: &AHashSet<T, S>) -> bool {
+        self.len() <= other.len() && self.iter().all(|v| other.contains(v))
+    }
+
+    /// Returns `true` if the set is a superset of another, i.e., `self` contains at least all the values in `other`.
+    #[inline]
+    pub fn is_superset(&self, other: &AHashSet<T, S>) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Adds a value to the set, returning whether the value was newly inserted.
+    ///
+    /// If the set already contained an equal value, it is not updated.
+    #[inline]
+    pub fn insert(&mut self, value: T) -> bool {
+        self.0.insert(value, ()).is_none()
+    }
+
+    /// Adds a value to the set, replacing the existing equal value, if any, and returning it.
+    #[inline]
+    pub fn replace(&mut self, value: T) -> Option<T> {
+        match self.0.replace_key(value) {
+            Ok(old) => Some(old),
+            Err(value) => {
+                self.0.insert(value, ());
+                None
+            }
+        }
+    }
+
+    /// Removes a value from the set, returning whether the value was present in the set.
+    #[inline]
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.remove(value).is_some()
+    }
+
+    /// Removes and returns the value in the set, if any, that is equal to the given one.
+    #[inline]
+    pub fn take<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.remove_entry(value).map(|(k, _)| k)
+    }
+}
+
+impl<T, S> AHashSet<T, S> {
+    /// Returns the number of elements the set can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// An iterator visiting all elements in arbitrary order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter(self.0.keys())
+    }
+
+    /// Returns the number of elements in the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the set contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Clears the set, returning all elements as an iterator. Keeps the allocated memory for reuse.
+    #[inline]
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        Drain(self.0.drain())
+    }
+
+    /// Retains only the elements specified by the predicate.
+    #[inline]
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.0.retain(|k, _| f(k))
+    }
+
+    /// Clears the set, removing all values. Keeps the allocated memory for reuse.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+
+    /// Returns a reference to the set's [`BuildHasher`].
+    #[inline]
+    pub fn hasher(&self) -> &S {
+        self.0.hasher()
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for AHashSet<T>
+where
+    T: Eq + Hash,
+{
+    fn from(arr: [T; N]) -> Self {
+        AHashSet::from_iter(arr)
+    }
+}
+
+impl<T, S> PartialEq for AHashSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    fn eq(&self, other: &AHashSet<T, S>) -> bool {
+        self.len() == other.len() && self.is_subset(other)
+    }
+}
+
+impl<T, S> Eq for AHashSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+}
+
+impl<T, S> BitOr<&AHashSet<T, S>> for &AHashSet<T, S>
+where
+    T: Eq + Hash + Clone,
+    S: BuildHasher + Default,
+{
+    type Output = AHashSet<T, S>;
+
+    /// Returns the union of `self` and `rhs` as a new `AHashSet<T, S>`.
+    fn bitor(self, rhs: &AHashSet<T, S>) -> AHashSet<T, S> {
+        self.union(rhs).cloned().collect()
+    }
+}
+
+impl<T, S> BitAnd<&AHashSet<T, S>> for &AHashSet<T, S>
+where
+    T: Eq + Hash + Clone,
+    S: BuildHasher + Default,
+{
+    type Output = AHashSet<T, S>;
+
+    /// Returns the intersection of `self` and `rhs` as a new `AHashSet<T, S>`.
+    fn bitand(self, rhs: &AHashSet<T, S>) -> AHashSet<T, S> {
+        self.intersection(rhs).cloned().collect()
+    }
+}
+
+impl<T, S> BitXor<&AHashSet<T, S>> for &AHashSet<T, S>
+where
+    T: Eq + Hash + Clone,
+    S: BuildHasher + Default,
+{
+    type Output = AHashSet<T, S>;
+
+    /// Returns the symmetric difference of `self` and `rhs` as a new `AHashSet<T, S>`.
+    fn bitxor(self, rhs: &AHashSet<T, S>) -> AHashSet<T, S> {
+        self.symmetric_difference(rhs).cloned().collect()
+    }
+}
+
+impl<T, S> Sub<&AHashSet<T, S>> for &AHashSet<T, S>
+where
+    T: Eq + Hash + Clone,
+    S: BuildHasher + Default,
+{
+    type Output = AHashSet<T, S>;
+
+    /// Returns the difference of `self` and `rhs` as a new `AHashSet<T, S>`.
+    fn sub(self, rhs: &AHashSet<T, S>) -> AHashSet<T, S> {
+        self.difference(rhs).cloned().collect()
+    }
+}
+
+impl<T, S> Debug for AHashSet<T, S>
+where
+    T: Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T, S> FromIterator<T> for AHashSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> AHashSet<T, S> {
+        AHashSet(iter.into_iter().map(|v| (v, ())).collect())
+    }
+}
+
+impl<'a, T, S> IntoIterator for &'a AHashSet<T, S> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, S> IntoIterator for AHashSet<T, S> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.0.into_keys())
+    }
+}
+
+impl<T, S> Extend<T> for AHashSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.0.extend(iter.into_iter().map(|v| (v, ())))
+    }
+}
+
+impl<'a, T, S> Extend<&'a T> for AHashSet<T, S>
+where
+    T: 'a + Eq + Hash + Copy,
+    S: BuildHasher,
+{
+    #[inline]
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied())
+    }
+}
+
+impl<T, S> Default for AHashSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    /// Creates an empty `AHashSet<T, S>` with the `Default` value for the hasher.
+    #[inline]
+    fn default() -> AHashSet<T, S> {
+        AHashSet(AHashMap::default())
+    }
+}
+
+/// An iterator over the values of an [`AHashSet`]. Created by [`AHashSet::iter`].
+pub struct Iter<'a, T>(Keys<'a, T, ()>);
+
+/// An owning iterator over the values of an [`AHashSet`]. Created by its `into_iter` method.
+pub struct IntoIter<T>(IntoKeys<T, ()>);
+
+/// A draining iterator over the values of an [`AHashSet`]. Created by [`AHashSet::drain`].
+pub struct Drain<'a, T>(MapDrain<'a, T, ()>);
+
+/// An iterator over the values in one set but not another. Created by [`AHashSet::difference`].
+pub struct Difference<'a, T, S> {
+    iter: Iter<'a, T>,
+    other: &'a AHashSet<T, S>,
+}
+
+/// An iterator over the values in both of two sets. Created by [`AHashSet::intersection`].
+pub struct Intersection<'a, T, S> {
+    iter: Iter<'a, T>,
+    other: &'a AHashSet<T, S>,
+}
+
+/// An iterator over the values in exactly one of two sets. Created by [`AHashSet::symmetric_difference`].
+pub struct SymmetricDifference<'a, T, S>(Chain<Difference<'a, T, S>, Difference<'a, T, S>>);
+
+/// An iterator over the values in either of two sets. Created by [`AHashSet::union`].
+pub struct Union<'a, T, S>(Chain<Iter<'a, T>, Difference<'a, T, S>>);
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        self.0.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        self.0.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> Iterator for Drain<'_, T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        self.0.next().map(|(k, _)| k)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> ExactSizeIterator for IntoIter<T> {}
+impl<T> ExactSizeIterator for Drain<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+impl<T> FusedIterator for IntoIter<T> {}
+impl<T> FusedIterator for Drain<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Iter(self.0.clone())
+    }
+}
+
+impl<'a, T, S> Iterator for Difference<'a, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        let other = self.other;
+        self.iter.find(|v| !other.contains(*v))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+impl<'a, T, S> Iterator for Intersection<'a, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        let other = self.other;
+        self.iter.find(|v| other.contains(*v))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+impl<'a, T, S> Iterator for SymmetricDifference<'a, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        self.0.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, T, S> Iterator for Union<'a, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        self.0.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T, S> FusedIterator for Difference<'_, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+}
+
+impl<T, S> FusedIterator for Intersection<'_, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+}
+
+impl<T, S> FusedIterator for SymmetricDifference<'_, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+}
+
+impl<T, S> FusedIterator for Union<'_, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+}
@@ -0,0 +1,53 @@
+use core::hash::Hasher;
+
+/// The number of keys that are hashed at the same time.
+/// Each key is hashed using a separate copy of the hasher, so there are no dependencies between the lanes. This allows
+/// the CPU to pipeline the multiply instructions of several keys. (The AES hasher has its own version of `hash_u64s`
+/// which interleaves the individual AES rounds.)
+const LANES: usize = 4;
+
+/// Hashes each of the `keys` as if by `write_u64` followed by `finish` on a clone of `hasher`, storing the results
+/// in `out`. (`keys` and `out` must be of the same length.)
+#[inline(always)]
+pub(crate) fn hash_u64s<H: Hasher + Clone>(hasher: &H, keys: &[u64], out: &mut [u64]) {
+    debug_assert_eq!(keys.len(), out.len());
+    let mut key_chunks = keys.chunks_exact(LANES);
+    let mut out_chunks = out.chunks_exact_mut(LANES);
+    for (keys, out) in (&mut key_chunks).zip(&mut out_chunks) {
+        let mut lanes = [hasher.clone(), hasher.clone(), hasher.clone(), hasher.clone()];
+        for (lane, key) in lanes.iter_mut().zip(keys) {
+            lane.write_u64(*key);
+        }
+        for (lane, out) in lanes.iter().zip(out.iter_mut()) {
+            *out = lane.finish();
+        }
+    }
+    for (key, out) in key_chunks.remainder().iter().zip(out_chunks.into_remainder()) {
+        let mut lane = hasher.clone();
+        lane.write_u64(*key);
+        *out = lane.finish();
+    }
+}
+
+/// Hashes each of the `keys` as if by `write` followed by `finish` on a clone of `hasher`, storing the results
+/// in `out`. (`keys` and `out` must be of the same length.)
+#[inline(always)]
+pub(crate) fn hash_slices<H: Hasher + Clone>(hasher: &H, keys: &[&[u8]], out: &mut [u64]) {
+    debug_assert_eq!(keys.len(), out.len());
+    let mut key_chunks = keys.chunks_exact(LANES);
+    let mut out_chunks = out.chunks_exact_mut(LANES);
+    for (keys, out) in (&mut key_chunks).zip(&mut out_chunks) {
+        let mut lanes = [hasher.clone(), hasher.clone(), hasher.clone(), hasher.clone()];
+        for (lane, key) in lanes.iter_mut().zip(keys) {
+            lane.write(key);
+        }
+        for (lane, out) in lanes.iter().zip(out.iter_mut()) {
+            *out = lane.finish();
+        }
+    }
+    for (key, out) in key_chunks.remainder().iter().zip(out_chunks.into_remainder()) {
+        let mut lane = hasher.clone();
+        lane.write(key);
+        *out = lane.finish();
+    }
+}
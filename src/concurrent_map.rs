@@ -0,0 +1,545 @@
+//! `ConcurrentAHashMap`, a hash map which can be shared between threads, and the types its methods return.
+
+use crate::AHashMap;
+use std::borrow::Borrow;
+use std::collections::hash_map::Entry;
+use std::fmt::{self, Debug};
+use std::hash::{BuildHasher, Hash};
+use std::iter::FromIterator;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::{thread, vec};
+
+/// The number of shards created per available CPU by [`ConcurrentAHashMap::new`].
+const SHARDS_PER_CPU: usize = 4;
+
+/// The number of high bits of the hash that are not used to select a shard.
+///
+/// `HashMap` uses the top 7 bits of the hash to filter the entries it compares within a group, so using them to select
+/// a shard would make them identical for every key in a shard.
+const SKIPPED_BITS: u32 = 7;
+
+/// A hash map which can be read and modified concurrently from many threads.
+///
+/// The entries are split between a number of shards, each of which is an [`AHashMap`] protected by its own `RwLock`.
+/// The shard for a key is selected by the high bits of its hash, so operations on keys in different shards never
+/// contend with each other, and readers of the same shard can proceed in parallel. All of the shards use the same
+/// hasher, which by default is a [`RandomState`](crate::RandomState) so an attacker cannot direct all of the keys to
+/// one shard.
+///
+/// Because a lock is held for the duration of each operation, the methods take `&self` and values are returned by
+/// cloning them or passed to a closure rather than returned by reference. A thread must not access the map from
+/// within such a closure, as this may deadlock.
+///
+/// A panic while a shard is locked does not make it unusable: the shard's map is always left in a valid state, so
+/// lock poisoning is ignored.
+///
+/// # Example
+///
+/// ```
+/// use ahash::ConcurrentAHashMap;
+/// use std::thread;
+///
+/// let counts: ConcurrentAHashMap<u32, u32> = ConcurrentAHashMap::new();
+/// thread::scope(|s| {
+///     for _ in 0..4 {
+///         s.spawn(|| {
+///             for i in 0..100 {
+///                 counts.update(i % 10, |entry| *entry.or_insert(0) += 1);
+///             }
+///         });
+///     }
+/// });
+/// assert_eq!(10, counts.len());
+/// assert_eq!(Some(40), counts.get(&3));
+/// ```
+pub struct ConcurrentAHashMap<K, V, S = crate::RandomState> {
+    shards: Box<[RwLock<AHashMap<K, V, S>>]>,
+    /// The amount the hash is shifted right (after removing `SKIPPED_BITS`) to obtain the shard index.
+    shift: u32,
+    hash_builder: S,
+}
+
+impl<K, V, S> ConcurrentAHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Clone + Default,
+{
+    /// Creates an empty map with a number of shards chosen based on the number of available CPUs.
+    pub fn new() -> Self {
+        Self::with_hasher(S::default())
+    }
+
+    /// Creates an empty map with the given number of shards.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shard_amount` is not a power of two greater than 1, or is too large to be selected by the hash.
+    pub fn with_shard_amount(shard_amount: usize) -> Self {
+        Self::with_shard_amount_and_hasher(shard_amount, S::default())
+    }
+}
+
+impl<K, V, S> ConcurrentAHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Clone,
+{
+    /// Creates an empty map which uses the given hasher, with a number of shards chosen based on the number of
+    /// available CPUs.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        let cpus = thread::available_parallelism().map_or(1, |n| n.get());
+        let shard_amount = (cpus * SHARDS_PER_CPU)
+            .next_power_of_two()
+            .min(1 << (64 - SKIPPED_BITS));
+        Self::with_shard_amount_and_hasher(shard_amount, hash_builder)
+    }
+
+    /// Creates an empty map with the given number of shards which uses the given hasher.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shard_amount` is not a power of two greater than 1, or is too large to be selected by the hash.
+    pub fn with_shard_amount_and_hasher(shard_amount: usize, hash_builder: S) -> Self {
+        assert!(shard_amount > 1, "shard_amount must be greater than 1");
+        assert!(shard_amount.is_power_of_two(), "shard_amount must be a power of two");
+        let bits = shard_amount.trailing_zeros();
+        assert!(bits <= 64 - SKIPPED_BITS, "shard_amount is too large");
+        let shards = (0..shard_amount)
+            .map(|_| RwLock::new(AHashMap::with_hasher(hash_builder.clone())))
+            .collect();
+        ConcurrentAHashMap {
+            shards,
+            shift: 64 - bits,
+            hash_builder,
+        }
+    }
+
+    #[inline]
+    fn shard_index<Q: Hash + ?Sized>(&self, k: &Q) -> usize {
+        let hash = self.hash_builder.hash_one(k);
+        ((hash << SKIPPED_BITS) >> self.shift) as usize
+    }
+
+    #[inline]
+    fn read_shard<Q: Hash + ?Sized>(&self, k: &Q) -> RwLockReadGuard<'_, AHashMap<K, V, S>> {
+        read(&self.shards[self.shard_index(k)])
+    }
+
+    #[inline]
+    fn write_shard<Q: Hash + ?Sized>(&self, k: &Q) -> RwLockWriteGuard<'_, AHashMap<K, V, S>> {
+        write(&self.shards[self.shard_index(k)])
+    }
+
+    /// Inserts a key-value pair into the map, returning the previous value if the key was present.
+    #[inline]
+    pub fn insert(&self, k: K, v: V) -> Option<V> {
+        self.write_shard(&k).insert(k, v)
+    }
+
+    /// Returns a clone of the value corresponding to the key.
+    #[inline]
+    pub fn get<Q>(&self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        V: Clone,
+    {
+        self.read_shard(k).get(k).cloned()
+    }
+
+    /// Calls `f` with a reference to the value corresponding to the key, and returns its result.
+    ///
+    /// The shard containing the key is locked for reading while `f` runs.
+    #[inline]
+    pub fn get_with<Q, F, R>(&self, k: &Q, f: F) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        F: FnOnce(&V) -> R,
+    {
+        self.read_shard(k).get(k).map(f)
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    #[inline]
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.read_shard(k).contains_key(k)
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key was previously in the map.
+    #[inline]
+    pub fn remove<Q>(&self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.write_shard(k).remove(k)
+    }
+
+    /// Calls `f` with the [`Entry`] for the key, and returns its result.
+    ///
+    /// The shard containing the key is locked for writing while `f` runs, so this can be used to atomically read and
+    /// modify the value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ahash::ConcurrentAHashMap;
+    ///
+    /// let map: ConcurrentAHashMap<&str, Vec<u32>> = ConcurrentAHashMap::new();
+    /// map.update("a", |entry| entry.or_default().push(1));
+    /// let len = map.update("a", |entry| {
+    ///     let values = entry.or_default();
+    ///     values.push(2);
+    ///     values.len()
+    /// });
+    /// assert_eq!(2, len);
+    /// ```
+    #[inline]
+    pub fn update<F, R>(&self, k: K, f: F) -> R
+    where
+        F: FnOnce(Entry<'_, K, V>) -> R,
+    {
+        f(self.write_shard(&k).entry(k))
+    }
+}
+
+impl<K, V, S> ConcurrentAHashMap<K, V, S> {
+    /// Returns the number of shards the entries are divided between.
+    #[inline]
+    pub fn shard_amount(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Returns the number of elements in the map.
+    ///
+    /// The shards are counted one at a time, so if other threads are modifying the map the result may not correspond
+    /// to its contents at any one point in time.
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|shard| read(shard).len()).sum()
+    }
+
+    /// Returns `true` if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(|shard| read(shard).is_empty())
+    }
+
+    /// Removes all the entries.
+    pub fn clear(&self) {
+        for shard in self.shards.iter() {
+            write(shard).clear();
+        }
+    }
+
+    /// Retains only the elements specified by the predicate.
+    ///
+    /// Each shard is locked for writing in turn while `f` is called on its entries.
+    pub fn retain<F>(&self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        for shard in self.shards.iter() {
+            write(shard).retain(&mut f);
+        }
+    }
+
+    /// Calls `f` on every entry in the map.
+    ///
+    /// Each shard is locked for reading in turn while `f` is called on its entries.
+    pub fn for_each<F>(&self, mut f: F)
+    where
+        F: FnMut(&K, &V),
+    {
+        for shard in self.shards.iter() {
+            read(shard).iter().for_each(|(k, v)| f(k, v));
+        }
+    }
+
+    /// An iterator visiting clones of all key-value pairs in arbitrary order.
+    ///
+    /// The iterator locks one shard at a time, only for as long as it takes to clone its entries. Entries inserted or
+    /// removed concurrently may or may not be visited.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, K, V, S>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        Iter {
+            shards: self.shards.iter(),
+            current: Vec::new().into_iter(),
+        }
+    }
+
+    /// Returns a reference to the map's [`BuildHasher`].
+    #[inline]
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Consumes the map, returning its shards.
+    pub fn into_shards(self) -> Vec<AHashMap<K, V, S>> {
+        Vec::from(self.shards)
+            .into_iter()
+            .map(|shard| shard.into_inner().unwrap_or_else(|e| e.into_inner()))
+            .collect()
+    }
+}
+
+#[inline]
+fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(|e| e.into_inner())
+}
+
+#[inline]
+fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(|e| e.into_inner())
+}
+
+impl<K, V, S> Debug for ConcurrentAHashMap<K, V, S>
+where
+    K: Debug,
+    V: Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let mut map = fmt.debug_map();
+        for shard in self.shards.iter() {
+            map.entries(read(shard).iter());
+        }
+        map.finish()
+    }
+}
+
+impl<K, V, S> Default for ConcurrentAHashMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher + Clone + Default,
+{
+    #[inline]
+    fn default() -> ConcurrentAHashMap<K, V, S> {
+        ConcurrentAHashMap::new()
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for ConcurrentAHashMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher + Clone + Default,
+{
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let map = ConcurrentAHashMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V, S> ConcurrentAHashMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher + Clone,
+{
+    /// Inserts all of the key-value pairs from the iterator.
+    ///
+    /// This is like [`Extend::extend`], but only needs a shared reference to the map.
+    pub fn extend<T: IntoIterator<Item = (K, V)>>(&self, iter: T) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K, V, S> IntoIterator for ConcurrentAHashMap<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.into_shards().into_iter().flatten())
+    }
+}
+
+/// An iterator over clones of the entries of a [`ConcurrentAHashMap`]. Created by [`ConcurrentAHashMap::iter`].
+pub struct Iter<'a, K, V, S> {
+    shards: std::slice::Iter<'a, RwLock<AHashMap<K, V, S>>>,
+    /// The entries cloned from the most recently visited shard which have not yet been returned.
+    current: vec::IntoIter<(K, V)>,
+}
+
+impl<K: Clone, V: Clone, S> Iterator for Iter<'_, K, V, S> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        loop {
+            if let Some(entry) = self.current.next() {
+                return Some(entry);
+            }
+            let shard = read(self.shards.next()?);
+            self.current = shard
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect::<Vec<_>>()
+                .into_iter();
+        }
+    }
+}
+
+/// An owning iterator over the entries of a [`ConcurrentAHashMap`]. Created by its `into_iter` method.
+pub struct IntoIter<K, V, S>(std::iter::Flatten<vec::IntoIter<AHashMap<K, V, S>>>);
+
+impl<K, V, S> Iterator for IntoIter<K, V, S> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, V)> {
+        self.0.next()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::RandomState;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Barrier;
+
+    const THREADS: usize = 8;
+
+    #[test]
+    fn test_single_threaded() {
+        let map: ConcurrentAHashMap<String, u32> = ConcurrentAHashMap::with_shard_amount(4);
+        assert_eq!(4, map.shard_amount());
+        assert!(map.is_empty());
+        assert_eq!(None, map.insert("a".to_string(), 1));
+        assert_eq!(Some(1), map.insert("a".to_string(), 2));
+        map.insert("b".to_string(), 3);
+        assert_eq!(Some(2), map.get("a"));
+        assert_eq!(Some(6), map.get_with("b", |v| v * 2));
+        assert!(map.contains_key("b"));
+        assert_eq!(Some(3), map.remove("b"));
+        assert_eq!(None, map.remove("b"));
+        assert_eq!(1, map.len());
+        assert_eq!("{\"a\": 2}", format!("{:?}", map));
+        map.clear();
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_keys_are_spread_between_shards() {
+        let map: ConcurrentAHashMap<u64, u64> = (0..10_000).map(|i| (i, i)).collect();
+        let shards = map.into_shards();
+        let expected = 10_000 / shards.len();
+        for shard in shards {
+            assert!(shard.len() > expected / 2, "{} {}", shard.len(), expected);
+            assert!(shard.len() < expected * 2, "{} {}", shard.len(), expected);
+        }
+    }
+
+    #[test]
+    fn test_iteration() {
+        let map = ConcurrentAHashMap::with_shard_amount_and_hasher(8, RandomState::with_seeds(1, 2));
+        map.extend((0..1000).map(|i| (i, i * 2)));
+        let mut entries: Vec<_> = map.iter().collect();
+        entries.sort_unstable();
+        assert_eq!((0..1000).map(|i| (i, i * 2)).collect::<Vec<_>>(), entries);
+        let mut sum = 0;
+        map.for_each(|k, v| sum += v - k);
+        assert_eq!((0..1000).sum::<i32>(), sum);
+        map.retain(|k, v| {
+            *v += 1;
+            k % 2 == 0
+        });
+        let mut entries: Vec<_> = map.into_iter().collect();
+        entries.sort_unstable();
+        assert_eq!(
+            (0..1000).step_by(2).map(|i| (i, i * 2 + 1)).collect::<Vec<_>>(),
+            entries
+        );
+    }
+
+    #[test]
+    fn test_concurrent_inserts_and_removes() {
+        let map: ConcurrentAHashMap<usize, usize> = ConcurrentAHashMap::with_shard_amount(16);
+        let barrier = Barrier::new(THREADS);
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                let (map, barrier) = (&map, &barrier);
+                s.spawn(move || {
+                    barrier.wait();
+                    for i in 0..10_000 {
+                        let key = i * THREADS + t;
+                        assert_eq!(None, map.insert(key, t));
+                        assert_eq!(Some(t), map.get(&key));
+                        if i % 2 == 1 {
+                            assert_eq!(Some(t), map.remove(&key));
+                        }
+                    }
+                });
+            }
+        });
+        assert_eq!(THREADS * 5_000, map.len());
+        map.for_each(|k, v| {
+            assert_eq!(k % THREADS, *v);
+            assert_eq!(0, (k / THREADS) % 2);
+        });
+    }
+
+    #[test]
+    fn test_concurrent_updates_are_atomic() {
+        let map: ConcurrentAHashMap<u32, u64> = ConcurrentAHashMap::with_shard_amount(2);
+        let barrier = Barrier::new(THREADS);
+        thread::scope(|s| {
+            for _ in 0..THREADS {
+                s.spawn(|| {
+                    barrier.wait();
+                    for i in 0..20_000 {
+                        map.update(i % 100, |entry| *entry.or_insert(0) += 1);
+                    }
+                });
+            }
+        });
+        assert_eq!(100, map.len());
+        map.for_each(|_, v| assert_eq!(THREADS as u64 * 200, *v));
+    }
+
+    #[test]
+    fn test_readers_and_writers() {
+        let map: ConcurrentAHashMap<u32, (u32, u32)> = ConcurrentAHashMap::new();
+        map.extend((0..64).map(|i| (i, (0, 0))));
+        let done = AtomicUsize::new(0);
+        thread::scope(|s| {
+            for _ in 0..THREADS / 2 {
+                s.spawn(|| {
+                    for n in 1..=5_000 {
+                        map.insert(n % 64, (n, n));
+                    }
+                    done.fetch_add(1, Ordering::Release);
+                });
+                s.spawn(|| {
+                    // Each value is written as a whole, so readers never observe a partially updated one.
+                    while done.load(Ordering::Acquire) < THREADS / 2 {
+                        for (_, (a, b)) in map.iter() {
+                            assert_eq!(a, b);
+                        }
+                    }
+                });
+            }
+        });
+        assert_eq!(64, map.len());
+    }
+
+    #[test]
+    fn test_poisoned_shard_is_still_usable() {
+        let map: ConcurrentAHashMap<u32, u32> = ConcurrentAHashMap::with_shard_amount(2);
+        map.insert(1, 1);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            map.update(1, |_| panic!("Intentional panic"));
+        }));
+        assert!(result.is_err());
+        assert_eq!(Some(1), map.get(&1));
+        map.insert(2, 2);
+        assert_eq!(2, map.len());
+    }
+}
@@ -43,6 +43,7 @@ convert!([u128; 2], [u64; 4]);
 convert!([u128; 2], [u32; 8]);
 convert!([u128; 2], [u16; 16]);
 convert!([u128; 2], [u8; 32]);
+convert!([u64; 4], [u8; 32]);
 convert!(u128, [u64; 2]);
 convert!(u128, [u32; 4]);
 convert!(u128, [u16; 8]);
@@ -82,6 +83,7 @@ macro_rules! as_array {
     }};
 }
 
+#[allow(dead_code)] // Some methods are only used by the AES hasher.
 pub(crate) trait ReadFromSlice {
     fn read_u16(&self) -> (u16, &[u8]);
     fn read_u32(&self) -> (u32, &[u8]);
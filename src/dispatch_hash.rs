@@ -0,0 +1,404 @@
+use crate::aes_hash;
+use crate::fallback_hash;
+use crate::stream::BlockHasher;
+#[cfg(feature = "specialize")]
+use crate::HasherExt;
+use core::hash::Hasher;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+const UNKNOWN: u8 = 0;
+const AES: u8 = 1;
+const FALLBACK: u8 = 2;
+
+/// The result of checking which instructions the CPU supports. This is only checked once.
+static BACKEND: AtomicU8 = AtomicU8::new(UNKNOWN);
+
+/// Returns true if the CPU that is currently running supports the instructions needed by the AES hasher.
+#[inline]
+pub(crate) fn aes_available() -> bool {
+    match BACKEND.load(Ordering::Relaxed) {
+        AES => true,
+        FALLBACK => false,
+        _ => {
+            let available = is_x86_feature_detected!("aes") && is_x86_feature_detected!("ssse3");
+            BACKEND.store(if available { AES } else { FALLBACK }, Ordering::Relaxed);
+            available
+        }
+    }
+}
+
+/// A `Hasher` for hashing an arbitrary stream of bytes.
+///
+/// Instances of [`AHasher`] represent state that is updated while hashing data.
+///
+/// Each method updates the internal state based on the new data provided. Once
+/// all of the data has been provided, the resulting hash can be obtained by calling
+/// `finish()`
+///
+/// This version checks at runtime if the CPU supports the AES instructions, and uses them if it does. Otherwise it
+/// uses the fallback algorithm. The check is only performed once, so all hashers within a process use the same
+/// algorithm.
+///
+/// [Clone] is also provided in case you wish to calculate hashes for two different items that
+/// start with the same data.
+///
+#[derive(Debug, Clone)]
+pub struct AHasher(Backend);
+
+#[derive(Debug, Clone)]
+enum Backend {
+    Aes(aes_hash::AHasher),
+    Fallback(fallback_hash::AHasher),
+}
+
+impl AHasher {
+    /// Creates a new hasher keyed to the provided keys.
+    ///
+    /// Normally hashers are created via `AHasher::default()` for fixed keys or `RandomState::new()` for randomly
+    /// generated keys and `RandomState::with_seeds(a,b)` for seeds that are set and can be reused. All of these work at
+    /// map creation time (and hence don't have any overhead on a per-item bais).
+    ///
+    /// This method directly creates the hasher instance and performs no transformation on the provided seeds. This may
+    /// be useful where a HashBuilder is not desired, such as for testing purposes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::hash::Hasher;
+    /// use ahash::AHasher;
+    ///
+    /// let mut hasher = AHasher::new_with_keys(1234, 5678);
+    ///
+    /// hasher.write_u32(1989);
+    /// hasher.write_u8(11);
+    /// hasher.write_u8(9);
+    /// hasher.write(b"Huh?");
+    ///
+    /// println!("Hash is {:x}!", hasher.finish());
+    /// ```
+    #[inline]
+    pub fn new_with_keys(key1: u128, key2: u128) -> Self {
+        if aes_available() {
+            Self::new_aes(key1, key2)
+        } else {
+            Self::new_fallback(key1, key2)
+        }
+    }
+
+    /// Creates a hasher using the AES algorithm. This must only be called if `aes_available()` returns true.
+    #[inline]
+    pub(crate) fn new_aes(key1: u128, key2: u128) -> Self {
+        AHasher(Backend::Aes(aes_hash::AHasher::new_with_keys(key1, key2)))
+    }
+
+    #[inline]
+    pub(crate) fn new_fallback(key1: u128, key2: u128) -> Self {
+        AHasher(Backend::Fallback(fallback_hash::AHasher::new_with_keys(key1, key2)))
+    }
+
+    /// Returns the full 128 bit hash of the data written so far.
+    ///
+    /// The lower 64 bits are the same as the value returned by `finish()`. See the documentation of the
+    /// `aes` and `fallback` versions of this method for how the upper bits are obtained.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::hash::Hasher;
+    /// use ahash::AHasher;
+    ///
+    /// let mut hasher = AHasher::new_with_keys(1234, 5678);
+    /// hasher.write(b"Some data");
+    ///
+    /// assert_eq!(hasher.finish(), hasher.finish_u128() as u64);
+    /// ```
+    #[inline]
+    pub fn finish_u128(&self) -> u128 {
+        match &self.0 {
+            Backend::Aes(hasher) => unsafe { aes_finish_u128(hasher) },
+            Backend::Fallback(hasher) => hasher.finish_u128(),
+        }
+    }
+
+    /// Hashes each of the `keys` as if by `write_u64` followed by `finish` on a clone of this hasher.
+    #[inline]
+    pub(crate) fn hash_u64_batch(&self, keys: &[u64], out: &mut [u64]) {
+        match &self.0 {
+            Backend::Aes(hasher) => unsafe { aes_hash_u64_batch(hasher, keys, out) },
+            Backend::Fallback(hasher) => hasher.hash_u64_batch(keys, out),
+        }
+    }
+
+    /// Hashes each of the `keys` as if by `write` followed by `finish` on a clone of this hasher.
+    #[inline]
+    pub(crate) fn hash_slice_batch(&self, keys: &[&[u8]], out: &mut [u64]) {
+        match &self.0 {
+            Backend::Aes(hasher) => unsafe { aes_hash_slice_batch(hasher, keys, out) },
+            Backend::Fallback(hasher) => hasher.hash_slice_batch(keys, out),
+        }
+    }
+}
+
+// These wrappers allow the AES hasher's methods (and the intrinsics they use) to be inlined into a function that
+// is compiled with the needed target features. They are only safe to call if `aes_available()` returned true,
+// which is guaranteed by `Backend::Aes` only being constructed via `new_aes`.
+
+#[target_feature(enable = "aes,ssse3")]
+unsafe fn aes_write(hasher: &mut aes_hash::AHasher, input: &[u8]) {
+    hasher.write(input)
+}
+
+#[target_feature(enable = "aes,ssse3")]
+unsafe fn aes_write_u64(hasher: &mut aes_hash::AHasher, i: u64) {
+    hasher.write_u64(i)
+}
+
+#[target_feature(enable = "aes,ssse3")]
+unsafe fn aes_write_u128(hasher: &mut aes_hash::AHasher, i: u128) {
+    hasher.write_u128(i)
+}
+
+#[target_feature(enable = "aes,ssse3")]
+unsafe fn aes_finish_u128(hasher: &aes_hash::AHasher) -> u128 {
+    hasher.finish_u128()
+}
+
+#[target_feature(enable = "aes,ssse3")]
+unsafe fn aes_hash_u64_batch(hasher: &aes_hash::AHasher, keys: &[u64], out: &mut [u64]) {
+    hasher.hash_u64_batch(keys, out)
+}
+
+#[target_feature(enable = "aes,ssse3")]
+unsafe fn aes_hash_slice_batch(hasher: &aes_hash::AHasher, keys: &[&[u8]], out: &mut [u64]) {
+    hasher.hash_slice_batch(keys, out)
+}
+
+#[target_feature(enable = "aes,ssse3")]
+unsafe fn aes_update_blocks(hasher: &aes_hash::AHasher, blocks: &mut aes_hash::Blocks, data: &[u8]) {
+    hasher.update_blocks(blocks, data)
+}
+
+#[target_feature(enable = "aes,ssse3")]
+unsafe fn aes_finish_blocks(
+    hasher: &aes_hash::AHasher,
+    blocks: aes_hash::Blocks,
+    tail: &[u8; 64],
+    length: u64,
+) -> aes_hash::AHasher {
+    hasher.finish_blocks(blocks, tail, length)
+}
+
+#[cfg(feature = "specialize")]
+#[target_feature(enable = "aes,ssse3")]
+unsafe fn aes_hash_u64(hasher: aes_hash::AHasher, value: u64) -> u64 {
+    hasher.hash_u64(value)
+}
+
+#[cfg(feature = "specialize")]
+#[target_feature(enable = "aes,ssse3")]
+unsafe fn aes_short_finish(hasher: &aes_hash::AHasher) -> u64 {
+    hasher.short_finish()
+}
+
+#[cfg(feature = "specialize")]
+impl HasherExt for AHasher {
+    #[inline]
+    fn hash_u64(self, value: u64) -> u64 {
+        match self.0 {
+            Backend::Aes(hasher) => unsafe { aes_hash_u64(hasher, value) },
+            Backend::Fallback(hasher) => hasher.hash_u64(value),
+        }
+    }
+
+    #[inline]
+    fn short_finish(&self) -> u64 {
+        match &self.0 {
+            Backend::Aes(hasher) => unsafe { aes_short_finish(hasher) },
+            Backend::Fallback(hasher) => hasher.short_finish(),
+        }
+    }
+}
+
+/// The block state of whichever algorithm the hasher uses.
+#[derive(Debug, Clone)]
+pub(crate) enum Blocks {
+    Aes(aes_hash::Blocks),
+    Fallback(fallback_hash::AHasher),
+}
+
+impl BlockHasher for AHasher {
+    type Blocks = Blocks;
+
+    #[inline]
+    fn start_blocks(&self) -> Blocks {
+        match &self.0 {
+            Backend::Aes(hasher) => Blocks::Aes(hasher.start_blocks()),
+            Backend::Fallback(hasher) => Blocks::Fallback(hasher.start_blocks()),
+        }
+    }
+
+    #[inline]
+    fn update_blocks(&self, blocks: &mut Blocks, data: &[u8]) {
+        match (&self.0, blocks) {
+            (Backend::Aes(hasher), Blocks::Aes(blocks)) => unsafe { aes_update_blocks(hasher, blocks, data) },
+            (Backend::Fallback(hasher), Blocks::Fallback(blocks)) => hasher.update_blocks(blocks, data),
+            _ => unreachable!("Blocks are always created by the same hasher"),
+        }
+    }
+
+    #[inline]
+    fn finish_blocks(&self, blocks: Blocks, tail: &[u8; 64], length: u64) -> AHasher {
+        match (&self.0, blocks) {
+            (Backend::Aes(hasher), Blocks::Aes(blocks)) => {
+                AHasher(Backend::Aes(unsafe { aes_finish_blocks(hasher, blocks, tail, length) }))
+            }
+            (Backend::Fallback(hasher), Blocks::Fallback(blocks)) => {
+                AHasher(Backend::Fallback(hasher.finish_blocks(blocks, tail, length)))
+            }
+            _ => unreachable!("Blocks are always created by the same hasher"),
+        }
+    }
+}
+
+/// Provides methods to hash all of the primitive types.
+impl Hasher for AHasher {
+    #[inline]
+    fn write_u8(&mut self, i: u8) {
+        self.write_u64(i as u64);
+    }
+
+    #[inline]
+    fn write_u16(&mut self, i: u16) {
+        self.write_u64(i as u64);
+    }
+
+    #[inline]
+    fn write_u32(&mut self, i: u32) {
+        self.write_u64(i as u64);
+    }
+
+    #[inline]
+    fn write_u64(&mut self, i: u64) {
+        match &mut self.0 {
+            Backend::Aes(hasher) => unsafe { aes_write_u64(hasher, i) },
+            Backend::Fallback(hasher) => hasher.write_u64(i),
+        }
+    }
+
+    #[inline]
+    fn write_u128(&mut self, i: u128) {
+        match &mut self.0 {
+            Backend::Aes(hasher) => unsafe { aes_write_u128(hasher, i) },
+            Backend::Fallback(hasher) => hasher.write_u128(i),
+        }
+    }
+
+    #[inline]
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    #[inline]
+    fn write(&mut self, input: &[u8]) {
+        match &mut self.0 {
+            Backend::Aes(hasher) => unsafe { aes_write(hasher, input) },
+            Backend::Fallback(hasher) => hasher.write(input),
+        }
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        match &self.0 {
+            Backend::Aes(hasher) => unsafe { aes_finish_u128(hasher) as u64 },
+            Backend::Fallback(hasher) => hasher.finish(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes_hash;
+    use crate::fallback_hash;
+    use crate::RandomState;
+    use std::hash::{BuildHasher, Hash, Hasher};
+
+    const INPUTS: [&str; 7] = [
+        "",
+        "a",
+        "abcd",
+        "abcdefgh",
+        "abcdefghijklmnop",
+        "abcdefghijklmnopqrstuvwxyz012345",
+        "abcdefghijklmnopqrstuvwxyz0123456789abcdefghijklmnopqrstuvwxyz0123456789",
+    ];
+
+    fn hash_with<H: Hasher>(mut hasher: H, input: &str, num: u64) -> u64 {
+        input.hash(&mut hasher);
+        hasher.write_u8(num as u8);
+        hasher.write_u64(num);
+        hasher.write_u128(num as u128);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_detection_is_cached() {
+        let available = aes_available();
+        assert_ne!(UNKNOWN, BACKEND.load(Ordering::Relaxed));
+        assert_eq!(available, aes_available());
+    }
+
+    #[test]
+    fn test_forced_fallback_matches_fallback() {
+        for (num, input) in INPUTS.iter().enumerate() {
+            let dispatched = AHasher::new_fallback(1234, 5678);
+            let direct = fallback_hash::AHasher::new_with_keys(1234, 5678);
+            assert_eq!(hash_with(dispatched, input, num as u64), hash_with(direct, input, num as u64));
+
+            let mut dispatched = AHasher::new_fallback(1234, 5678);
+            let mut direct = fallback_hash::AHasher::new_with_keys(1234, 5678);
+            dispatched.write(input.as_bytes());
+            direct.write(input.as_bytes());
+            assert_eq!(direct.finish_u128(), dispatched.finish_u128());
+        }
+    }
+
+    #[test]
+    fn test_forced_aes_matches_aes() {
+        if !aes_available() {
+            return;
+        }
+        for (num, input) in INPUTS.iter().enumerate() {
+            let dispatched = AHasher::new_aes(1234, 5678);
+            let direct = aes_hash::AHasher::new_with_keys(1234, 5678);
+            assert_eq!(hash_with(dispatched, input, num as u64), hash_with(direct, input, num as u64));
+
+            let mut dispatched = AHasher::new_aes(1234, 5678);
+            let mut direct = aes_hash::AHasher::new_with_keys(1234, 5678);
+            dispatched.write(input.as_bytes());
+            direct.write(input.as_bytes());
+            assert_eq!(direct.finish_u128(), dispatched.finish_u128());
+        }
+    }
+
+    #[test]
+    fn test_backends_differ() {
+        if !aes_available() {
+            return;
+        }
+        let mut aes = AHasher::new_aes(1234, 5678);
+        let mut fallback = AHasher::new_fallback(1234, 5678);
+        "test".hash(&mut aes);
+        "test".hash(&mut fallback);
+        assert_ne!(aes.finish(), fallback.finish());
+    }
+
+    #[test]
+    fn test_build_hasher_uses_detected_backend() {
+        let hasher = RandomState::with_seeds(1, 2).build_hasher();
+        match hasher.0 {
+            Backend::Aes(_) => assert!(aes_available()),
+            Backend::Fallback(_) => assert!(!aes_available()),
+        }
+    }
+}
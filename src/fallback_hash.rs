@@ -1,5 +1,6 @@
 use crate::convert::*;
 use crate::operations::folded_multiply;
+use crate::stream::BlockHasher;
 #[cfg(feature = "specialize")]
 use crate::HasherExt;
 use core::hash::Hasher;
@@ -50,6 +51,49 @@ impl AHasher {
         }
     }
 
+    /// Returns a 128 bit hash of the data written so far.
+    ///
+    /// The lower 64 bits are the same as the value returned by `finish()`. The upper 64 bits are a second, independent
+    /// finalization of the state which uses the `extra_keys` in place of `pad`, so neither half can be computed from
+    /// the other without knowing the keys.
+    ///
+    /// Note that the fallback algorithm only maintains 64 bits of internal state, so unlike the AES version this
+    /// does not make collisions between different inputs any less likely than they are with `finish()`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::hash::Hasher;
+    /// use ahash::AHasher;
+    ///
+    /// let mut hasher = AHasher::new_with_keys(1234, 5678);
+    /// hasher.write(b"Some data");
+    ///
+    /// assert_eq!(hasher.finish(), hasher.finish_u128() as u64);
+    /// ```
+    #[inline]
+    #[allow(dead_code)] // Is not called if non-fallback hash is used.
+    pub fn finish_u128(&self) -> u128 {
+        let rot = (self.buffer & 63) as u32;
+        let low = folded_multiply(self.buffer, self.pad).rotate_left(rot);
+        let high = folded_multiply(self.buffer ^ self.extra_keys[0], self.extra_keys[1]).rotate_left(rot ^ 32);
+        ((high as u128) << 64) | low as u128
+    }
+
+    /// Hashes each of the `keys` as if by `write_u64` followed by `finish` on a clone of this hasher.
+    #[inline]
+    #[allow(dead_code)] // Is not called if non-fallback hash is used.
+    pub(crate) fn hash_u64_batch(&self, keys: &[u64], out: &mut [u64]) {
+        crate::batch::hash_u64s(self, keys, out)
+    }
+
+    /// Hashes each of the `keys` as if by `write` followed by `finish` on a clone of this hasher.
+    #[inline]
+    #[allow(dead_code)] // Is not called if non-fallback hash is used.
+    pub(crate) fn hash_slice_batch(&self, keys: &[&[u8]], out: &mut [u64]) {
+        crate::batch::hash_slices(self, keys, out)
+    }
+
     /// This update function has the goal of updating the buffer with a single multiply
     /// FxHash does this but is vulnerable to attack. To avoid this input needs to be masked to with an
     /// unpredictable value. Other hashes such as murmurhash have taken this approach but were found vulnerable
@@ -103,6 +147,36 @@ impl AHasher {
     }
 }
 
+/// The loop used by `write` for inputs longer than 16 bytes only depends on `buffer`, so a copy of the hasher is used
+/// as the state.
+impl BlockHasher for AHasher {
+    type Blocks = AHasher;
+
+    #[inline]
+    fn start_blocks(&self) -> AHasher {
+        self.clone()
+    }
+
+    #[inline]
+    fn update_blocks(&self, blocks: &mut AHasher, mut data: &[u8]) {
+        while !data.is_empty() {
+            let (block, rest) = data.read_u128();
+            blocks.large_update(block);
+            data = rest;
+        }
+    }
+
+    #[inline]
+    fn finish_blocks(&self, mut blocks: AHasher, tail: &[u8; 64], length: u64) -> AHasher {
+        let tail: [u128; 4] = (*tail).convert();
+        for block in tail.iter() {
+            blocks.large_update(*block);
+        }
+        blocks.buffer = blocks.buffer.wrapping_add(length).wrapping_mul(MULTIPLE);
+        blocks
+    }
+}
+
 #[cfg(feature = "specialize")]
 impl HasherExt for AHasher {
     #[inline]
@@ -136,7 +210,7 @@ impl Hasher for AHasher {
 
     #[inline]
     fn write_u64(&mut self, i: u64) {
-        self.update(i as u64);
+        self.update(i);
     }
 
     #[inline]
@@ -181,7 +255,7 @@ impl Hasher for AHasher {
                     self.update(value.convert());
                 }
             } else {
-                if data.len() > 0 {
+                if !data.is_empty() {
                     self.update(data[0] as u64);
                 }
             }
@@ -214,6 +288,21 @@ mod tests {
         assert_ne!(hex::encode(result), hex::encode(result2));
     }
 
+    #[test]
+    fn test_upper_half_is_not_derived_from_lower() {
+        let a = AHasher {
+            buffer: 1,
+            pad: 2,
+            extra_keys: [3, 4],
+        };
+        let b = AHasher {
+            extra_keys: [5, 6],
+            ..a.clone()
+        };
+        assert_eq!(a.finish_u128() as u64, b.finish_u128() as u64);
+        assert_ne!(a.finish_u128() >> 64, b.finish_u128() >> 64);
+    }
+
     #[test]
     fn test_conversion() {
         let input: &[u8] = "dddddddd".as_bytes();
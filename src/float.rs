@@ -0,0 +1,197 @@
+use core::fmt::{self, Debug, Display};
+use core::hash::{Hash, Hasher};
+
+macro_rules! hashable_float {
+    ($name:ident, $float:ty, $bits:ty) => {
+        /// A wrapper around a float which implements [Hash] and [Eq], so that it can be used as a key in a map.
+        ///
+        /// Before hashing the value is canonicalised: `-0.0` is treated as `0.0`, and all NaNs are treated as the
+        /// same value regardless of their sign or payload. Equality follows the same rules, so unlike the float
+        /// itself NaN is equal to NaN, which is what is required for it to be found again in a map.
+        /// All other values are compared and hashed by their bits.
+        ///
+        /// The canonical bits are hashed with a single call to [Hasher::write_u64].
+        ///
+        /// # Example
+        ///
+        /// ```
+        #[doc = concat!("use ahash::{RandomState, ", stringify!($name), "};")]
+        ///
+        /// let state = RandomState::with_seeds(1, 2);
+        #[doc = concat!("let zero = state.hash_one(", stringify!($name), "(0.0));")]
+        #[doc = concat!("assert_eq!(zero, state.hash_one(", stringify!($name), "(-0.0)));")]
+        #[doc = concat!("assert_eq!(", stringify!($name), "(", stringify!($float), "::NAN), ", stringify!($name), "(-", stringify!($float), "::NAN));")]
+        /// ```
+        #[derive(Clone, Copy, Default)]
+        #[repr(transparent)]
+        pub struct $name(pub $float);
+
+        impl $name {
+            /// Returns the bits which are hashed and compared: those of the value with `-0.0` replaced by `0.0` and
+            /// any NaN replaced by the standard NaN.
+            #[inline]
+            pub fn to_canonical_bits(self) -> $bits {
+                if self.0 == 0.0 {
+                    0
+                } else if self.0.is_nan() {
+                    <$float>::NAN.to_bits()
+                } else {
+                    self.0.to_bits()
+                }
+            }
+        }
+
+        impl Hash for $name {
+            #[inline]
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                state.write_u64(self.to_canonical_bits() as u64);
+            }
+        }
+
+        impl PartialEq for $name {
+            #[inline]
+            fn eq(&self, other: &Self) -> bool {
+                self.to_canonical_bits() == other.to_canonical_bits()
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl From<$float> for $name {
+            #[inline]
+            fn from(value: $float) -> Self {
+                $name(value)
+            }
+        }
+
+        impl From<$name> for $float {
+            #[inline]
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                Debug::fmt(&self.0, f)
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                Display::fmt(&self.0, f)
+            }
+        }
+    };
+}
+
+hashable_float!(HashableF32, f32, u32);
+hashable_float!(HashableF64, f64, u64);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{AHasher, CallHasher, RandomState};
+
+    fn hash<T: Hash>(value: T) -> u64 {
+        value.get_hash(AHasher::new_with_keys(1, 2))
+    }
+
+    #[test]
+    fn test_zeros_are_equal() {
+        assert_eq!(HashableF64(0.0), HashableF64(-0.0));
+        assert_eq!(hash(HashableF64(0.0)), hash(HashableF64(-0.0)));
+        assert_eq!(HashableF32(0.0), HashableF32(-0.0));
+        assert_eq!(hash(HashableF32(0.0)), hash(HashableF32(-0.0)));
+    }
+
+    #[test]
+    fn test_nans_are_equal() {
+        let nans64 = [
+            f64::NAN,
+            -f64::NAN,
+            f64::from_bits(0x7ff0_0000_0000_0001),
+            f64::from_bits(0xfff8_dead_beef_0000),
+            (-1.0_f64).sqrt(),
+        ];
+        for nan in nans64.iter() {
+            assert!(nan.is_nan());
+            assert_eq!(HashableF64(f64::NAN), HashableF64(*nan));
+            assert_eq!(hash(HashableF64(f64::NAN)), hash(HashableF64(*nan)));
+        }
+        let nans32 = [f32::NAN, -f32::NAN, f32::from_bits(0x7f80_0001), f32::from_bits(0xffc0_beef)];
+        for nan in nans32.iter() {
+            assert!(nan.is_nan());
+            assert_eq!(HashableF32(f32::NAN), HashableF32(*nan));
+            assert_eq!(hash(HashableF32(f32::NAN)), hash(HashableF32(*nan)));
+        }
+    }
+
+    #[test]
+    fn test_distinct_values_differ() {
+        let values = [
+            0.0,
+            1.0,
+            -1.0,
+            f64::MIN_POSITIVE,
+            -f64::MIN_POSITIVE,
+            f64::EPSILON,
+            f64::MAX,
+            f64::MIN,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NAN,
+            1.0 + f64::EPSILON,
+        ];
+        for (i, a) in values.iter().enumerate() {
+            for b in values[i + 1..].iter() {
+                assert_ne!(HashableF64(*a), HashableF64(*b), "{} {}", a, b);
+                assert_ne!(hash(HashableF64(*a)), hash(HashableF64(*b)), "{} {}", a, b);
+            }
+        }
+        let values = [
+            0.0,
+            1.0,
+            -1.0,
+            f32::MIN_POSITIVE,
+            f32::EPSILON,
+            f32::MAX,
+            f32::MIN,
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+            f32::NAN,
+            1.0 + f32::EPSILON,
+        ];
+        for (i, a) in values.iter().enumerate() {
+            for b in values[i + 1..].iter() {
+                assert_ne!(HashableF32(*a), HashableF32(*b), "{} {}", a, b);
+                assert_ne!(hash(HashableF32(*a)), hash(HashableF32(*b)), "{} {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hashes_canonical_bits() {
+        let state = RandomState::with_seeds(1, 2);
+        assert_eq!(state.hash_one(0_u64), state.hash_one(HashableF64(-0.0)));
+        assert_eq!(state.hash_one(1.5_f64.to_bits()), state.hash_one(HashableF64(1.5)));
+        assert_eq!(state.hash_one(f64::NAN.to_bits()), state.hash_one(HashableF64(-f64::NAN)));
+        assert_eq!(state.hash_one(1.5_f32.to_bits() as u64), state.hash_one(HashableF32(1.5)));
+        assert_eq!(f32::NAN.to_bits(), HashableF32(-f32::NAN).to_canonical_bits());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_map_lookup() {
+        use crate::AHashMap;
+
+        let mut map: AHashMap<HashableF64, &str> = AHashMap::new();
+        map.insert(HashableF64(-0.0), "zero");
+        map.insert(HashableF64(f64::from_bits(0x7ff0_0000_0000_0001)), "nan");
+        map.insert(HashableF64(2.5), "two and a half");
+        assert_eq!(Some(&"zero"), map.get(&HashableF64(0.0)));
+        assert_eq!(Some(&"nan"), map.get(&HashableF64(f64::NAN)));
+        assert_eq!(Some(&"two and a half"), map.get(&2.5.into()));
+        assert_eq!(None, map.get(&HashableF64(2.6)));
+    }
+}
@@ -0,0 +1,229 @@
+//! Simulates HashDoS attacks: known techniques for constructing inputs which collide regardless of the key, for
+//! hashers which use a key but mix the input in a way that an attacker can control.
+//!
+//! Each attack produces groups of inputs which are intended to all have the same hash. The tests check that every
+//! group does collide under a weak reference hasher, no matter which key it uses, and that with `AHasher` the same
+//! inputs collide no more than random values would. (Both when written as bytes and as a sequence of `write_u64`)
+use crate::hash_statistics_test::{test_both_backends, Report};
+use core::hash::Hasher;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// The number of words in each input.
+const WORDS: usize = 16;
+/// Each attack toggles one difference in each pair of words, so each group has `2^(WORDS / 2)` inputs.
+const GROUP_SIZE: usize = 1 << (WORDS / 2);
+
+/// FxHash (as used by rustc and Firefox) with the initial state used as a key.
+///
+/// Each word is combined with a multiply, which can never affect the bits below the differing one. So a difference
+/// in the highest bit of one word stays in the highest bit, is rotated to bit 4, and can be cancelled by the next
+/// word. The key does not matter.
+struct KeyedFxHasher {
+    hash: u64,
+}
+
+impl Hasher for KeyedFxHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(8) {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.write_u64(u64::from_le_bytes(word));
+        }
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ i).wrapping_mul(0x517c_c1b7_2722_0a95);
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+const MURMUR_MULTIPLE: u64 = 0xc6a4_a793_5bd1_e995;
+
+/// MurmurHash64A, which is seeded and "premixes" each block of input before combining it with the state.
+///
+/// The premixing does not depend on the seed and is reversible, so an attacker can choose the premixed values. A
+/// difference in the highest bit of one premixed block remains only in the highest bit after the multiply, and is
+/// cancelled by the same difference in the next block. (This is the attack described on `fallback_hash::update`)
+struct MurmurHash64A {
+    seed: u64,
+    data: Vec<u8>,
+}
+
+impl Hasher for MurmurHash64A {
+    fn write(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        let mut h = self.seed ^ (self.data.len() as u64).wrapping_mul(MURMUR_MULTIPLE);
+        let mut blocks = self.data.chunks_exact(8);
+        for block in &mut blocks {
+            let mut word = [0; 8];
+            word.copy_from_slice(block);
+            h ^= murmur_premix(u64::from_le_bytes(word));
+            h = h.wrapping_mul(MURMUR_MULTIPLE);
+        }
+        let tail = blocks.remainder();
+        if !tail.is_empty() {
+            for (i, byte) in tail.iter().enumerate() {
+                h ^= (*byte as u64) << (8 * i);
+            }
+            h = h.wrapping_mul(MURMUR_MULTIPLE);
+        }
+        h ^= h >> 47;
+        h = h.wrapping_mul(MURMUR_MULTIPLE);
+        h ^ (h >> 47)
+    }
+}
+
+fn murmur_premix(block: u64) -> u64 {
+    let k = block.wrapping_mul(MURMUR_MULTIPLE);
+    (k ^ (k >> 47)).wrapping_mul(MURMUR_MULTIPLE)
+}
+
+/// The inverse of `murmur_premix`. (`x ^ x >> 47` is its own inverse, and an odd number has a multiplicative inverse)
+fn murmur_unpremix(premixed: u64) -> u64 {
+    let mut inverse = MURMUR_MULTIPLE;
+    for _ in 0..5 {
+        inverse = inverse.wrapping_mul(2_u64.wrapping_sub(MURMUR_MULTIPLE.wrapping_mul(inverse)));
+    }
+    let k = premixed.wrapping_mul(inverse);
+    (k ^ (k >> 47)).wrapping_mul(inverse)
+}
+
+/// Returns `groups` groups of inputs, each of which consists of a random input with every combination of the
+/// differences produced by `toggle` applied to each pair of words.
+fn attack(groups: usize, seed: u64, toggle: impl Fn(&mut [u64])) -> Vec<Vec<Vec<u64>>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..groups)
+        .map(|_| {
+            let base: Vec<u64> = (0..WORDS).map(|_| rng.gen()).collect();
+            (0..GROUP_SIZE)
+                .map(|mask| {
+                    let mut words = base.clone();
+                    for (pair, words) in words.chunks_mut(2).enumerate() {
+                        if mask & (1 << pair) != 0 {
+                            toggle(words);
+                        }
+                    }
+                    words
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn fx_attack(groups: usize) -> Vec<Vec<Vec<u64>>> {
+    attack(groups, 1, |pair| {
+        pair[0] ^= 1 << 63;
+        pair[1] ^= 1 << 4;
+    })
+}
+
+/// The words are chosen so that the premixed values have the differences, so this is the same for any seed.
+fn murmur_attack(groups: usize) -> Vec<Vec<Vec<u64>>> {
+    attack(groups, 2, |pair| {
+        pair[0] = murmur_unpremix(murmur_premix(pair[0]) ^ 1 << 63);
+        pair[1] = murmur_unpremix(murmur_premix(pair[1]) ^ 1 << 63);
+    })
+}
+
+fn to_bytes(words: &[u64]) -> Vec<u8> {
+    words.iter().flat_map(|w| w.to_le_bytes().to_vec()).collect()
+}
+
+fn hash_bytes<T: Hasher>(mut hasher: T, words: &[u64]) -> u64 {
+    hasher.write(&to_bytes(words));
+    hasher.finish()
+}
+
+fn hash_words<T: Hasher>(mut hasher: T, words: &[u64]) -> u64 {
+    for word in words {
+        hasher.write_u64(*word);
+    }
+    hasher.finish()
+}
+
+/// Returns the number of inputs whose hash equals that of an earlier input.
+fn full_collisions(hashes: &mut [u64]) -> usize {
+    hashes.sort_unstable();
+    hashes.windows(2).filter(|w| w[0] == w[1]).count()
+}
+
+/// Returns the largest number of hashes which land in the same bucket of a table with one bucket per hash.
+/// (As a hashmap would use them)
+fn worst_bucket(hashes: &[u64]) -> usize {
+    let buckets = hashes.len().next_power_of_two();
+    let mut counts = vec![0; buckets];
+    for hash in hashes {
+        counts[*hash as usize & (buckets - 1)] += 1;
+    }
+    counts.into_iter().max().unwrap()
+}
+
+/// Checks that every group of inputs collides under the reference hasher.
+fn assert_attack_succeeds<T: Hasher>(name: &str, hasher: impl Fn() -> T, groups: &[Vec<Vec<u64>>]) {
+    for group in groups {
+        let first = hash_bytes(hasher(), &group[0]);
+        for input in group.iter() {
+            assert_eq!(first, hash_bytes(hasher(), input), "{} attack failed against {:x?}", name, input);
+        }
+    }
+}
+
+/// Checks that the inputs of the attack do not collide more than random values would under `hasher`.
+fn assert_attack_fails<T: Hasher>(name: &str, hasher: impl Fn() -> T, groups: &[Vec<Vec<u64>>]) {
+    for (method, hash) in [("write", hash_bytes::<T> as fn(T, &[u64]) -> u64), ("write_u64", hash_words::<T>)].iter()
+    {
+        let mut hashes: Vec<u64> = groups.iter().flatten().map(|input| hash(hasher(), input)).collect();
+        Report {
+            test: format!("{} attack via {} worst bucket", name, method),
+            measure: "size",
+            worst: worst_bucket(&hashes) as f64,
+            // The bucket sizes are Poisson distributed with mean 1, so this is extremely unlikely.
+            limit: 16.0,
+        }
+        .check();
+        Report {
+            test: format!("{} attack via {} full collisions", name, method),
+            measure: "count",
+            worst: full_collisions(&mut hashes) as f64,
+            limit: 0.0,
+        }
+        .check();
+    }
+}
+
+#[test]
+fn test_attacks_break_weak_hashers() {
+    let fx = fx_attack(64);
+    let murmur = murmur_attack(64);
+    let mut rng = StdRng::seed_from_u64(3);
+    for _ in 0..8 {
+        let key: u64 = rng.gen();
+        assert_attack_succeeds("FxHash", || KeyedFxHasher { hash: key }, &fx);
+        assert_attack_succeeds("MurmurHash", || MurmurHash64A { seed: key, data: Vec::new() }, &murmur);
+    }
+    let mut hashes: Vec<u64> = fx.iter().flatten().map(|input| hash_words(KeyedFxHasher { hash: 1 }, input)).collect();
+    assert_eq!(GROUP_SIZE, worst_bucket(&hashes));
+    assert_eq!(fx.len() * (GROUP_SIZE - 1), full_collisions(&mut hashes));
+}
+
+fn test_attacks_fail<T: Hasher>(constructor: impl Fn(u64, u64) -> T) {
+    let fx = fx_attack(256);
+    let murmur = murmur_attack(256);
+    for (k0, k1) in [(0, 0), (1, 2), (12345, 67890), (u64::MAX, u64::MAX)].iter() {
+        assert_attack_fails("FxHash", || constructor(*k0, *k1), &fx);
+        assert_attack_fails("MurmurHash", || constructor(*k0, *k1), &murmur);
+    }
+}
+
+test_both_backends! {
+    fn resists_attacks() {
+        test_attacks_fail(AHasher::test_with_keys);
+    }
+}
@@ -1,5 +1,6 @@
 use std::borrow::Borrow;
-use std::collections::{hash_map, HashMap};
+use std::collections::hash_map::{self, Drain, Entry, IntoKeys, IntoValues, Iter, IterMut, Keys, Values, ValuesMut};
+use std::collections::{HashMap, TryReserveError};
 use std::fmt::{self, Debug};
 use std::hash::{BuildHasher, Hash};
 use std::iter::FromIterator;
@@ -8,6 +9,9 @@ use std::panic::UnwindSafe;
 
 /// A [`HashMap`](std::collections::HashMap) using [`RandomState`](crate::RandomState) to hash the items.
 /// Requires the `std` feature to be enabled.
+///
+/// The methods of `HashMap` are provided directly, and the underlying `HashMap` is also accessible via `Deref`. It can be
+/// converted to and from a `HashMap` with the same hasher using `From`/`Into`.
 #[derive(Clone)]
 pub struct AHashMap<K, V, S = crate::RandomState>(HashMap<K, V, S>);
 
@@ -37,6 +41,199 @@ where
     pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
         AHashMap(HashMap::with_capacity_and_hasher(capacity, hash_builder))
     }
+
+    /// Reserves capacity for at least `additional` more elements. (See [`HashMap::reserve`])
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new allocation size overflows `usize`.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional)
+    }
+
+    /// Tries to reserve capacity for at least `additional` more elements, returning an error instead of panicking if
+    /// the allocation fails. (See [`HashMap::try_reserve`])
+    #[inline]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.0.try_reserve(additional)
+    }
+
+    /// Shrinks the capacity of the map as much as possible.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit()
+    }
+
+    /// Shrinks the capacity of the map to the larger of `min_capacity` and what is needed for the current entries.
+    #[inline]
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.0.shrink_to(min_capacity)
+    }
+
+    /// Gets the given key's corresponding entry in the map for in-place manipulation.
+    #[inline]
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        self.0.entry(key)
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.get(k)
+    }
+
+    /// Returns the key-value pair corresponding to the supplied key.
+    #[inline]
+    pub fn get_key_value<Q>(&self, k: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.get_key_value(k)
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    #[inline]
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.contains_key(k)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.get_mut(k)
+    }
+
+    /// Inserts a key-value pair into the map.
+    ///
+    /// If the map did not have this key present, `None` is returned. Otherwise the value is updated and the old value
+    /// is returned. The key is not updated. (See [`HashMap::insert`])
+    #[inline]
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        self.0.insert(k, v)
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key was previously in the map.
+    #[inline]
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.remove(k)
+    }
+
+    /// Removes a key from the map, returning the stored key and value if the key was previously in the map.
+    #[inline]
+    pub fn remove_entry<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.remove_entry(k)
+    }
+}
+
+impl<K, V, S> AHashMap<K, V, S> {
+    /// Returns the number of elements the map can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// An iterator visiting all keys in arbitrary order.
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        self.0.keys()
+    }
+
+    /// Creates a consuming iterator visiting all the keys in arbitrary order.
+    #[inline]
+    pub fn into_keys(self) -> IntoKeys<K, V> {
+        self.0.into_keys()
+    }
+
+    /// An iterator visiting all values in arbitrary order.
+    #[inline]
+    pub fn values(&self) -> Values<'_, K, V> {
+        self.0.values()
+    }
+
+    /// An iterator visiting all values mutably in arbitrary order.
+    #[inline]
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        self.0.values_mut()
+    }
+
+    /// Creates a consuming iterator visiting all the values in arbitrary order.
+    #[inline]
+    pub fn into_values(self) -> IntoValues<K, V> {
+        self.0.into_values()
+    }
+
+    /// An iterator visiting all key-value pairs in arbitrary order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.0.iter()
+    }
+
+    /// An iterator visiting all key-value pairs in arbitrary order, with mutable references to the values.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        self.0.iter_mut()
+    }
+
+    /// Returns the number of elements in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the map contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Clears the map, returning all key-value pairs as an iterator. Keeps the allocated memory for reuse.
+    #[inline]
+    pub fn drain(&mut self) -> Drain<'_, K, V> {
+        self.0.drain()
+    }
+
+    /// Retains only the elements specified by the predicate.
+    #[inline]
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.0.retain(f)
+    }
+
+    /// Clears the map, removing all key-value pairs. Keeps the allocated memory for reuse.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+
+    /// Returns a reference to the map's [`BuildHasher`].
+    #[inline]
+    pub fn hasher(&self) -> &S {
+        self.0.hasher()
+    }
 }
 
 impl<K, V, S> Deref for AHashMap<K, V, S> {
@@ -52,6 +249,36 @@ impl<K, V, S> DerefMut for AHashMap<K, V, S> {
     }
 }
 
+impl<K, V, S> From<HashMap<K, V, S>> for AHashMap<K, V, S> {
+    fn from(item: HashMap<K, V, S>) -> Self {
+        AHashMap(item)
+    }
+}
+
+impl<K, V, S> From<AHashMap<K, V, S>> for HashMap<K, V, S> {
+    fn from(item: AHashMap<K, V, S>) -> Self {
+        item.0
+    }
+}
+
+impl<K, V, const N: usize> From<[(K, V); N]> for AHashMap<K, V>
+where
+    K: Eq + Hash,
+{
+    /// # Examples
+    ///
+    /// ```
+    /// use ahash::AHashMap;
+    ///
+    /// let map1 = AHashMap::from([(1, 2), (3, 4)]);
+    /// let map2: AHashMap<_, _> = [(1, 2), (3, 4)].into();
+    /// assert_eq!(map1, map2);
+    /// ```
+    fn from(arr: [(K, V); N]) -> Self {
+        AHashMap::from_iter(arr)
+    }
+}
+
 impl<K, V, S> UnwindSafe for AHashMap<K, V, S>
 where
     K: UnwindSafe,
@@ -120,17 +347,17 @@ where
 
 impl<'a, K, V, S> IntoIterator for &'a AHashMap<K, V, S> {
     type Item = (&'a K, &'a V);
-    type IntoIter = hash_map::Iter<'a, K, V>;
+    type IntoIter = Iter<'a, K, V>;
     fn into_iter(self) -> Self::IntoIter {
-        (&self.0).iter()
+        self.0.iter()
     }
 }
 
 impl<'a, K, V, S> IntoIterator for &'a mut AHashMap<K, V, S> {
     type Item = (&'a K, &'a mut V);
-    type IntoIter = hash_map::IterMut<'a, K, V>;
+    type IntoIter = IterMut<'a, K, V>;
     fn into_iter(self) -> Self::IntoIter {
-        (&mut self.0).iter_mut()
+        self.0.iter_mut()
     }
 }
 
@@ -175,3 +402,199 @@ where
         AHashMap::with_hasher(Default::default())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::hash_map::Entry::{Occupied, Vacant};
+
+    #[test]
+    fn test_create_capacity_zero() {
+        let mut m: AHashMap<i32, i32> = AHashMap::with_capacity(0);
+        assert!(m.insert(1, 1).is_none());
+        assert!(m.contains_key(&1));
+        assert!(!m.contains_key(&0));
+    }
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut m: AHashMap<i32, i32> = AHashMap::new();
+        assert_eq!(m.len(), 0);
+        assert!(m.insert(1, 2).is_none());
+        assert!(m.insert(2, 4).is_none());
+        assert_eq!(m.len(), 2);
+        assert_eq!(*m.get(&1).unwrap(), 2);
+        assert_eq!(m.get_key_value(&2), Some((&2, &4)));
+        assert_eq!(m.insert(1, 3), Some(2));
+        assert_eq!(m[&1], 3);
+        if let Some(x) = m.get_mut(&1) {
+            *x = 5;
+        }
+        assert_eq!(m.remove(&1), Some(5));
+        assert_eq!(m.remove(&1), None);
+        assert_eq!(m.remove_entry(&2), Some((2, 4)));
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn test_borrowed_lookup() {
+        let mut m: AHashMap<String, usize> = AHashMap::new();
+        m.insert("foo".to_string(), 3);
+        assert_eq!(m.get("foo"), Some(&3));
+        assert!(m.contains_key("foo"));
+        assert_eq!(m.remove("foo"), Some(3));
+    }
+
+    #[test]
+    fn test_entry() {
+        let mut m: AHashMap<i32, i32> = [(1, 10), (2, 20), (3, 30), (4, 40), (5, 50), (6, 60)].into();
+
+        match m.entry(1) {
+            Vacant(_) => unreachable!(),
+            Occupied(mut view) => {
+                assert_eq!(view.get(), &10);
+                assert_eq!(view.insert(100), 10);
+            }
+        }
+        assert_eq!(m.get(&1).unwrap(), &100);
+        assert_eq!(m.len(), 6);
+
+        match m.entry(3) {
+            Vacant(_) => unreachable!(),
+            Occupied(view) => {
+                assert_eq!(view.remove(), 30);
+            }
+        }
+        assert_eq!(m.get(&3), None);
+        assert_eq!(m.len(), 5);
+
+        match m.entry(10) {
+            Occupied(_) => unreachable!(),
+            Vacant(view) => {
+                assert_eq!(*view.insert(1000), 1000);
+            }
+        }
+        assert_eq!(m.get(&10).unwrap(), &1000);
+        assert_eq!(m.len(), 6);
+
+        *m.entry(11).or_insert(0) += 1;
+        *m.entry(11).or_insert(0) += 1;
+        assert_eq!(m[&11], 2);
+    }
+
+    #[test]
+    fn test_iterate() {
+        let mut m: AHashMap<i32, i32> = AHashMap::with_capacity(4);
+        for i in 0..32 {
+            assert!(m.insert(i, i * 2).is_none());
+        }
+        assert_eq!(m.len(), 32);
+        let mut observed: u32 = 0;
+        for (k, v) in m.iter() {
+            assert_eq!(*v, *k * 2);
+            observed |= 1 << *k;
+        }
+        assert_eq!(observed, 0xFFFF_FFFF);
+        for (_, v) in m.iter_mut() {
+            *v += 1;
+        }
+        for v in m.values_mut() {
+            *v -= 1;
+        }
+        let mut keys: Vec<_> = m.keys().cloned().collect();
+        keys.sort_unstable();
+        assert_eq!(keys, (0..32).collect::<Vec<_>>());
+        let mut values: Vec<_> = m.values().cloned().collect();
+        values.sort_unstable();
+        assert_eq!(values, (0..32).map(|i| i * 2).collect::<Vec<_>>());
+        let mut into_keys: Vec<_> = m.clone().into_keys().collect();
+        into_keys.sort_unstable();
+        assert_eq!(keys, into_keys);
+        let mut into_values: Vec<_> = m.into_values().collect();
+        into_values.sort_unstable();
+        assert_eq!(values, into_values);
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut map: AHashMap<i32, i32> = (0..100).map(|x| (x, x * 10)).collect();
+        map.retain(|&k, _| k % 2 == 0);
+        assert_eq!(map.len(), 50);
+        assert_eq!(map[&2], 20);
+        assert_eq!(map[&4], 40);
+        assert_eq!(map[&6], 60);
+    }
+
+    #[test]
+    fn test_drain_and_clear() {
+        let mut map: AHashMap<i32, i32> = (0..10).map(|x| (x, x)).collect();
+        let capacity = map.capacity();
+        let mut drained: Vec<_> = map.drain().collect();
+        drained.sort_unstable();
+        assert_eq!(drained, (0..10).map(|x| (x, x)).collect::<Vec<_>>());
+        assert!(map.is_empty());
+        assert_eq!(map.capacity(), capacity);
+        map.insert(1, 1);
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.capacity(), capacity);
+    }
+
+    #[test]
+    fn test_reserve_shrink_to_fit() {
+        let mut m: AHashMap<i32, i32> = AHashMap::new();
+        m.insert(0, 0);
+        m.remove(&0);
+        assert!(m.capacity() >= m.len());
+        for i in 0..128 {
+            m.insert(i, i);
+        }
+        m.reserve(256);
+        let usable_cap = m.capacity();
+        for i in 128..(128 + 256) {
+            m.insert(i, i);
+            assert_eq!(m.capacity(), usable_cap);
+        }
+        for i in 100..(128 + 256) {
+            assert_eq!(m.remove(&i), Some(i));
+        }
+        m.shrink_to(110);
+        assert!(m.capacity() >= 110);
+        m.shrink_to_fit();
+        assert_eq!(m.len(), 100);
+        assert!(m.capacity() >= m.len());
+        for i in 0..100 {
+            assert_eq!(m.remove(&i), Some(i));
+        }
+        m.shrink_to_fit();
+        m.insert(0, 0);
+        assert_eq!(m.len(), 1);
+    }
+
+    #[test]
+    fn test_try_reserve() {
+        let mut empty: AHashMap<u8, u8> = AHashMap::new();
+        assert!(empty.try_reserve(usize::MAX).is_err());
+        assert!(empty.try_reserve(usize::MAX / 16).is_err());
+        assert!(empty.try_reserve(16).is_ok());
+        assert!(empty.capacity() >= 16);
+    }
+
+    #[test]
+    fn test_hasher() {
+        let state = crate::RandomState::with_seeds(1, 2);
+        let map: AHashMap<u32, u32> = AHashMap::with_hasher(state.clone());
+        assert_eq!(map.hasher().hash_one(5), state.hash_one(5));
+    }
+
+    #[test]
+    fn test_conversions() {
+        let mut std_map: HashMap<i32, &str, crate::RandomState> = HashMap::default();
+        std_map.insert(1, "one");
+        std_map.insert(2, "two");
+        let map: AHashMap<i32, &str> = std_map.clone().into();
+        assert_eq!(map, AHashMap::from([(1, "one"), (2, "two")]));
+        let back: HashMap<i32, &str, crate::RandomState> = map.into();
+        assert_eq!(back, std_map);
+    }
+}
@@ -148,7 +148,7 @@ fn assert_each_byte_differs(base: u64, alternitives: Vec<u64>) {
     for alternitive in alternitives {
         changed_bits |= base ^ alternitive
     }
-    assert_eq!(core::u64::MAX, changed_bits, "Bits changed: {:x}", changed_bits);
+    assert_eq!(u64::MAX, changed_bits, "Bits changed: {:x}", changed_bits);
 }
 
 fn test_finish_is_consistent<T: Hasher>(constructor: impl Fn(u64, u64) -> T) {
@@ -266,16 +266,24 @@ fn test_padding_doesnot_collide<T: Hasher>(hasher: impl Fn() -> T) {
                 let (same_bytes, same_nibbles) = count_same_bytes_and_nibbles(value, long.finish());
                 assert!(
                     same_bytes <= 3,
-                    format!("{} bytes of {} -> {:x} vs {:x}", num, c, value, long.finish())
+                    "{} bytes of {} -> {:x} vs {:x}",
+                    num,
+                    c,
+                    value,
+                    long.finish()
                 );
                 assert!(
                     same_nibbles <= 8,
-                    format!("{} bytes of {} -> {:x} vs {:x}", num, c, value, long.finish())
+                    "{} bytes of {} -> {:x} vs {:x}",
+                    num,
+                    c,
+                    value,
+                    long.finish()
                 );
                 let flipped_bits = (value ^ long.finish()).count_ones();
                 assert!(flipped_bits > 10);
             }
-            if string.len() > 0 {
+            if !string.is_empty() {
                 let mut padded = string[1..].to_string();
                 padded.push(c as char);
                 for num in 2..=128 {
@@ -285,25 +293,21 @@ fn test_padding_doesnot_collide<T: Hasher>(hasher: impl Fn() -> T) {
                     let (same_bytes, same_nibbles) = count_same_bytes_and_nibbles(value, long.finish());
                     assert!(
                         same_bytes <= 3,
-                        format!(
-                            "string {:?} + {} bytes of {} -> {:x} vs {:x}",
-                            string,
-                            num,
-                            c,
-                            value,
-                            long.finish()
-                        )
+                        "string {:?} + {} bytes of {} -> {:x} vs {:x}",
+                        string,
+                        num,
+                        c,
+                        value,
+                        long.finish()
                     );
                     assert!(
                         same_nibbles <= 8,
-                        format!(
-                            "string {:?} + {} bytes of {} -> {:x} vs {:x}",
-                            string,
-                            num,
-                            c,
-                            value,
-                            long.finish()
-                        )
+                        "string {:?} + {} bytes of {} -> {:x} vs {:x}",
+                        string,
+                        num,
+                        c,
+                        value,
+                        long.finish()
                     );
                     let flipped_bits = (value ^ long.finish()).count_ones();
                     assert!(flipped_bits > 10);
@@ -313,6 +317,65 @@ fn test_padding_doesnot_collide<T: Hasher>(hasher: impl Fn() -> T) {
     }
 }
 
+/// Exposes the upper 64 bits of a 128 bit hash as the result of `finish()`.
+/// (The lower 64 bits are what `finish()` already returns.)
+/// This allows all of the above tests to be applied to both halves of the 128 bit output.
+#[derive(Clone)]
+struct UpperHalf<T: Hasher> {
+    hasher: T,
+    finish_u128: fn(&T) -> u128,
+}
+
+impl<T: Hasher> Hasher for UpperHalf<T> {
+    fn write(&mut self, bytes: &[u8]) {
+        self.hasher.write(bytes)
+    }
+    fn write_u8(&mut self, i: u8) {
+        self.hasher.write_u8(i)
+    }
+    fn write_u16(&mut self, i: u16) {
+        self.hasher.write_u16(i)
+    }
+    fn write_u32(&mut self, i: u32) {
+        self.hasher.write_u32(i)
+    }
+    fn write_u64(&mut self, i: u64) {
+        self.hasher.write_u64(i)
+    }
+    fn write_u128(&mut self, i: u128) {
+        self.hasher.write_u128(i)
+    }
+    fn write_usize(&mut self, i: usize) {
+        self.hasher.write_usize(i)
+    }
+    fn finish(&self) -> u64 {
+        ((self.finish_u128)(&self.hasher) >> 64) as u64
+    }
+}
+
+/// The default `hash_u64` defers back to `CallHasher`, so this must be provided for `UpperHalf` to be hashed via
+/// `get_hash` when specialization is enabled.
+#[cfg(feature = "specialize")]
+impl<T: Hasher> HasherExt for UpperHalf<T> {
+    fn hash_u64(mut self, value: u64) -> u64 {
+        self.write_u64(value);
+        self.finish()
+    }
+
+    fn short_finish(&self) -> u64 {
+        self.finish()
+    }
+}
+
+fn test_lower_half_is_finish<T: Hasher>(constructor: impl Fn(u64, u64) -> T, finish_u128: fn(&T) -> u128) {
+    for string in ["", "1", "1234", "12345678", "1234567812345678", "12345678123456781234567812345678"].iter() {
+        let mut hasher = constructor(1, 2);
+        string.hash(&mut hasher);
+        assert_eq!(hasher.finish(), finish_u128(&hasher) as u64);
+        assert_sufficiently_different(hasher.finish(), (finish_u128(&hasher) >> 64) as u64, 2);
+    }
+}
+
 #[cfg(test)]
 mod fallback_tests {
     use crate::fallback_hash::*;
@@ -372,14 +435,57 @@ mod fallback_tests {
         test_padding_doesnot_collide(|| AHasher::test_with_keys(1, 0));
         test_padding_doesnot_collide(|| AHasher::test_with_keys(1, 1));
     }
+    fn upper_half(hasher: AHasher) -> UpperHalf<AHasher> {
+        UpperHalf {
+            hasher,
+            finish_u128: AHasher::finish_u128,
+        }
+    }
+
+    #[test]
+    fn fallback_lower_half_is_finish() {
+        test_lower_half_is_finish(AHasher::test_with_keys, AHasher::finish_u128);
+    }
+
+    #[test]
+    fn fallback_upper_half_single_bit_flip() {
+        test_single_bit_flip(|| upper_half(AHasher::test_with_keys(0, 0)))
+    }
+
+    #[test]
+    fn fallback_upper_half_single_key_bit_flip() {
+        test_single_key_bit_flip(|k1, k2| upper_half(AHasher::test_with_keys(k1, k2)))
+    }
+
+    #[test]
+    fn fallback_upper_half_all_bytes_matter() {
+        test_all_bytes_matter(|| upper_half(AHasher::test_with_keys(0, 0)));
+    }
+
+    #[test]
+    fn fallback_upper_half_no_pair_collisions() {
+        test_no_pair_collisions(|| upper_half(AHasher::test_with_keys(0, 0)));
+    }
+
+    #[test]
+    fn fallback_upper_half_keys_change_output() {
+        test_keys_change_output(|k1, k2| upper_half(AHasher::test_with_keys(k1, k2)));
+    }
+
+    #[test]
+    fn fallback_upper_half_padding_doesnot_collide() {
+        test_padding_doesnot_collide(|| upper_half(AHasher::test_with_keys(0, 0)));
+        test_padding_doesnot_collide(|| upper_half(AHasher::test_with_keys(1, 1)));
+    }
 }
 
 ///Basic sanity tests of the cypto properties of aHash.
-#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "aes", not(miri)))]
+///(If AES-NI is not enabled, these run against the software implementation of AES)
 #[cfg(test)]
 mod aes_tests {
     use crate::aes_hash::*;
     use crate::hash_quality_test::*;
+    use crate::operations::aes_unavailable;
     use std::hash::{Hash, Hasher};
 
     const BAD_KEY: u64 = 0x5252_5252_5252_5252; //This encrypts to 0.
@@ -387,6 +493,9 @@ mod aes_tests {
 
     #[test]
     fn test_single_bit_in_byte() {
+        if aes_unavailable() {
+            return;
+        }
         let mut hasher1 = AHasher::new_with_keys(0, 0);
         8_u32.hash(&mut hasher1);
         let mut hasher2 = AHasher::new_with_keys(0, 0);
@@ -396,56 +505,153 @@ mod aes_tests {
 
     #[test]
     fn aes_single_bit_flip() {
+        if aes_unavailable() {
+            return;
+        }
         test_single_bit_flip(|| AHasher::test_with_keys(BAD_KEY, BAD_KEY));
         test_single_bit_flip(|| AHasher::test_with_keys(BAD_KEY2, BAD_KEY2));
     }
 
     #[test]
     fn aes_single_key_bit_flip() {
-        test_single_key_bit_flip(|k1, k2| AHasher::test_with_keys(k1, k2))
+        if aes_unavailable() {
+            return;
+        }
+        test_single_key_bit_flip(AHasher::test_with_keys)
     }
 
     #[test]
     fn aes_all_bytes_matter() {
+        if aes_unavailable() {
+            return;
+        }
         test_all_bytes_matter(|| AHasher::test_with_keys(BAD_KEY, BAD_KEY));
         test_all_bytes_matter(|| AHasher::test_with_keys(BAD_KEY2, BAD_KEY2));
     }
 
     #[test]
     fn aes_test_no_pair_collisions() {
+        if aes_unavailable() {
+            return;
+        }
         test_no_pair_collisions(|| AHasher::test_with_keys(BAD_KEY, BAD_KEY));
         test_no_pair_collisions(|| AHasher::test_with_keys(BAD_KEY2, BAD_KEY2));
     }
 
     #[test]
     fn ase_test_no_full_collisions() {
+        if aes_unavailable() {
+            return;
+        }
         test_no_full_collisions(|| AHasher::test_with_keys(12345, 67890));
     }
 
     #[test]
     fn aes_keys_change_output() {
+        if aes_unavailable() {
+            return;
+        }
         test_keys_change_output(AHasher::test_with_keys);
     }
 
     #[test]
     fn aes_input_affect_every_byte() {
+        if aes_unavailable() {
+            return;
+        }
         test_input_affect_every_byte(AHasher::test_with_keys);
     }
 
     #[test]
     fn aes_keys_affect_every_byte() {
+        if aes_unavailable() {
+            return;
+        }
         test_keys_affect_every_byte(0, AHasher::test_with_keys);
         test_keys_affect_every_byte("", AHasher::test_with_keys);
         test_keys_affect_every_byte((0, 0), AHasher::test_with_keys);
     }
     #[test]
     fn aes_finish_is_consistant() {
+        if aes_unavailable() {
+            return;
+        }
         test_finish_is_consistent(AHasher::test_with_keys)
     }
 
     #[test]
     fn aes_padding_doesnot_collide() {
+        if aes_unavailable() {
+            return;
+        }
         test_padding_doesnot_collide(|| AHasher::test_with_keys(BAD_KEY, BAD_KEY));
         test_padding_doesnot_collide(|| AHasher::test_with_keys(BAD_KEY2, BAD_KEY2));
     }
+
+    fn upper_half(hasher: AHasher) -> UpperHalf<AHasher> {
+        UpperHalf {
+            hasher,
+            finish_u128: AHasher::finish_u128,
+        }
+    }
+
+    #[test]
+    fn aes_lower_half_is_finish() {
+        if aes_unavailable() {
+            return;
+        }
+        test_lower_half_is_finish(AHasher::test_with_keys, AHasher::finish_u128);
+    }
+
+    #[test]
+    fn aes_upper_half_single_bit_flip() {
+        if aes_unavailable() {
+            return;
+        }
+        test_single_bit_flip(|| upper_half(AHasher::test_with_keys(BAD_KEY, BAD_KEY)));
+        test_single_bit_flip(|| upper_half(AHasher::test_with_keys(BAD_KEY2, BAD_KEY2)));
+    }
+
+    #[test]
+    fn aes_upper_half_single_key_bit_flip() {
+        if aes_unavailable() {
+            return;
+        }
+        test_single_key_bit_flip(|k1, k2| upper_half(AHasher::test_with_keys(k1, k2)))
+    }
+
+    #[test]
+    fn aes_upper_half_all_bytes_matter() {
+        if aes_unavailable() {
+            return;
+        }
+        test_all_bytes_matter(|| upper_half(AHasher::test_with_keys(BAD_KEY, BAD_KEY)));
+        test_all_bytes_matter(|| upper_half(AHasher::test_with_keys(BAD_KEY2, BAD_KEY2)));
+    }
+
+    #[test]
+    fn aes_upper_half_no_pair_collisions() {
+        if aes_unavailable() {
+            return;
+        }
+        test_no_pair_collisions(|| upper_half(AHasher::test_with_keys(BAD_KEY, BAD_KEY)));
+        test_no_pair_collisions(|| upper_half(AHasher::test_with_keys(BAD_KEY2, BAD_KEY2)));
+    }
+
+    #[test]
+    fn aes_upper_half_keys_change_output() {
+        if aes_unavailable() {
+            return;
+        }
+        test_keys_change_output(|k1, k2| upper_half(AHasher::test_with_keys(k1, k2)));
+    }
+
+    #[test]
+    fn aes_upper_half_padding_doesnot_collide() {
+        if aes_unavailable() {
+            return;
+        }
+        test_padding_doesnot_collide(|| upper_half(AHasher::test_with_keys(BAD_KEY, BAD_KEY)));
+        test_padding_doesnot_collide(|| upper_half(AHasher::test_with_keys(BAD_KEY2, BAD_KEY2)));
+    }
 }
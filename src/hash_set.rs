@@ -1,4 +1,6 @@
-use std::collections::{hash_set, HashSet};
+use std::borrow::Borrow;
+use std::collections::hash_set::{self, Difference, Drain, Intersection, Iter, SymmetricDifference, Union};
+use std::collections::{HashSet, TryReserveError};
 use std::fmt::{self, Debug};
 use std::hash::{BuildHasher, Hash};
 use std::iter::FromIterator;
@@ -6,6 +8,9 @@ use std::ops::{BitAnd, BitOr, BitXor, Deref, DerefMut, Sub};
 
 /// A [`HashSet`](std::collections::HashSet) using [`RandomState`](crate::RandomState) to hash the items.
 /// Requires the `std` feature to be enabled.
+///
+/// The methods of `HashSet` are provided directly, and the underlying `HashSet` is also accessible via `Deref`. It can be
+/// converted to and from a `HashSet` with the same hasher using `From`/`Into`.
 #[derive(Clone)]
 pub struct AHashSet<T, S = crate::RandomState>(HashSet<T, S>);
 
@@ -35,6 +40,215 @@ where
     pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
         AHashSet(HashSet::with_capacity_and_hasher(capacity, hash_builder))
     }
+
+    /// Reserves capacity for at least `additional` more elements. (See [`HashSet::reserve`])
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new allocation size overflows `usize`.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional)
+    }
+
+    /// Tries to reserve capacity for at least `additional` more elements, returning an error instead of panicking if
+    /// the allocation fails. (See [`HashSet::try_reserve`])
+    #[inline]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.0.try_reserve(additional)
+    }
+
+    /// Shrinks the capacity of the set as much as possible.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit()
+    }
+
+    /// Shrinks the capacity of the set to the larger of `min_capacity` and what is needed for the current values.
+    #[inline]
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.0.shrink_to(min_capacity)
+    }
+
+    /// Visits the values representing the difference, i.e., the values that are in `self` but not in `other`.
+    #[inline]
+    pub fn difference<'a>(&'a self, other: &'a HashSet<T, S>) -> Difference<'a, T, S> {
+        self.0.difference(other)
+    }
+
+    /// Visits the values representing the symmetric difference, i.e., the values that are in `self` or in `other`
+    /// but not in both.
+    #[inline]
+    pub fn symmetric_difference<'a>(&'a self, other: &'a HashSet<T, S>) -> SymmetricDifference<'a, T, S> {
+        self.0.symmetric_difference(other)
+    }
+
+    /// Visits the values representing the intersection, i.e., the values that are both in `self` and `other`.
+    #[inline]
+    pub fn intersection<'a>(&'a self, other: &'a HashSet<T, S>) -> Intersection<'a, T, S> {
+        self.0.intersection(other)
+    }
+
+    /// Visits the values representing the union, i.e., all the values in `self` or `other`, without duplicates.
+    #[inline]
+    pub fn union<'a>(&'a self, other: &'a HashSet<T, S>) -> Union<'a, T, S> {
+        self.0.union(other)
+    }
+
+    /// Returns `true` if the set contains a value.
+    #[inline]
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.contains(value)
+    }
+
+    /// Returns a reference to the value in the set, if any, that is equal to the given value.
+    #[inline]
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.get(value)
+    }
+
+    /// Returns `true` if `self` has no elements in common with `other`.
+    #[inline]
+    pub fn is_disjoint(&self, other: &HashSet<T, S>) -> bool {
+        self.0.is_disjoint(other)
+    }
+
+    /// Returns `true` if the set is a subset of another, i.e., `other` contains at least all the values in `self`.
+    #[inline]
+    pub fn is_subset(&self, other: &HashSet<T, S>) -> bool {
+        self.0.is_subset(other)
+    }
+
+    /// Returns `true` if the set is a superset of another, i.e., `self` contains at least all the values in `other`.
+    #[inline]
+    pub fn is_superset(&self, other: &HashSet<T, S>) -> bool {
+        self.0.is_superset(other)
+    }
+
+    /// Adds a value to the set, returning whether the value was newly inserted.
+    ///
+    /// If the set already contained an equal value, it is not updated. (See [`HashSet::insert`])
+    #[inline]
+    pub fn insert(&mut self, value: T) -> bool {
+        self.0.insert(value)
+    }
+
+    /// Adds a value to the set, replacing the existing equal value, if any, and returning it.
+    #[inline]
+    pub fn replace(&mut self, value: T) -> Option<T> {
+        self.0.replace(value)
+    }
+
+    /// Removes a value from the set, returning whether the value was present in the set.
+    #[inline]
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.remove(value)
+    }
+
+    /// Removes and returns the value in the set, if any, that is equal to the given one.
+    #[inline]
+    pub fn take<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.take(value)
+    }
+}
+
+impl<T, S> AHashSet<T, S> {
+    /// Returns the number of elements the set can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// An iterator visiting all elements in arbitrary order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.0.iter()
+    }
+
+    /// Returns the number of elements in the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the set contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Clears the set, returning all elements as an iterator. Keeps the allocated memory for reuse.
+    #[inline]
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        self.0.drain()
+    }
+
+    /// Retains only the elements specified by the predicate.
+    #[inline]
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.0.retain(f)
+    }
+
+    /// Clears the set, removing all values. Keeps the allocated memory for reuse.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+
+    /// Returns a reference to the set's [`BuildHasher`].
+    #[inline]
+    pub fn hasher(&self) -> &S {
+        self.0.hasher()
+    }
+}
+
+impl<T, S> From<HashSet<T, S>> for AHashSet<T, S> {
+    fn from(item: HashSet<T, S>) -> Self {
+        AHashSet(item)
+    }
+}
+
+impl<T, S> From<AHashSet<T, S>> for HashSet<T, S> {
+    fn from(item: AHashSet<T, S>) -> Self {
+        item.0
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for AHashSet<T>
+where
+    T: Eq + Hash,
+{
+    /// # Examples
+    ///
+    /// ```
+    /// use ahash::AHashSet;
+    ///
+    /// let set1 = AHashSet::from([1, 2, 3, 4]);
+    /// let set2: AHashSet<_> = [1, 2, 3, 4].into();
+    /// assert_eq!(set1, set2);
+    /// ```
+    fn from(arr: [T; N]) -> Self {
+        AHashSet::from_iter(arr)
+    }
 }
 
 impl<T, S> Deref for AHashSet<T, S> {
@@ -218,9 +432,9 @@ where
 
 impl<'a, T, S> IntoIterator for &'a AHashSet<T, S> {
     type Item = &'a T;
-    type IntoIter = hash_set::Iter<'a, T>;
+    type IntoIter = Iter<'a, T>;
     fn into_iter(self) -> Self::IntoIter {
-        (&self.0).iter()
+        self.0.iter()
     }
 }
 
@@ -265,3 +479,153 @@ where
         AHashSet(HashSet::default())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_insert_contains_remove() {
+        let mut s: AHashSet<i32> = AHashSet::new();
+        assert!(s.is_empty());
+        assert!(s.insert(1));
+        assert!(s.insert(2));
+        assert!(!s.insert(2));
+        assert_eq!(s.len(), 2);
+        assert!(s.contains(&1));
+        assert!(!s.contains(&3));
+        assert_eq!(s.get(&2), Some(&2));
+        assert!(s.remove(&1));
+        assert!(!s.remove(&1));
+        assert_eq!(s.take(&2), Some(2));
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn test_borrowed_lookup() {
+        let mut s: AHashSet<String> = AHashSet::new();
+        s.insert("foo".to_string());
+        assert!(s.contains("foo"));
+        assert_eq!(s.get("foo").map(String::as_str), Some("foo"));
+        assert!(s.remove("foo"));
+    }
+
+    #[test]
+    fn test_replace() {
+        #[derive(Debug)]
+        struct Foo(&'static str, i32);
+
+        impl PartialEq for Foo {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+
+        impl Eq for Foo {}
+
+        impl Hash for Foo {
+            fn hash<H: std::hash::Hasher>(&self, h: &mut H) {
+                self.0.hash(h);
+            }
+        }
+
+        let mut s: AHashSet<Foo> = AHashSet::new();
+        assert_eq!(s.replace(Foo("a", 1)), None);
+        assert_eq!(s.len(), 1);
+        assert_eq!(s.replace(Foo("a", 2)), Some(Foo("a", 1)));
+        assert_eq!(s.len(), 1);
+        assert_eq!(s.iter().next().unwrap().1, 2);
+    }
+
+    #[test]
+    fn test_disjoint_subset_superset() {
+        let a = AHashSet::from([0, 5, 11, 7]);
+        let mut b = AHashSet::from([7]);
+        assert!(!a.is_disjoint(&b));
+        assert!(b.is_subset(&a));
+        assert!(a.is_superset(&b));
+        assert!(b.remove(&7));
+        b.insert(2);
+        assert!(a.is_disjoint(&b));
+        assert!(!b.is_subset(&a));
+        assert!(!a.is_superset(&b));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = AHashSet::from([1, 3, 5, 9, 11, 16, 19, 24]);
+        let b = AHashSet::from([-2, 1, 5, 9, 13, 19]);
+
+        let mut intersection: Vec<_> = a.intersection(&b).cloned().collect();
+        intersection.sort_unstable();
+        assert_eq!(intersection, [1, 5, 9, 19]);
+
+        let mut difference: Vec<_> = a.difference(&b).cloned().collect();
+        difference.sort_unstable();
+        assert_eq!(difference, [3, 11, 16, 24]);
+
+        let mut symmetric_difference: Vec<_> = a.symmetric_difference(&b).cloned().collect();
+        symmetric_difference.sort_unstable();
+        assert_eq!(symmetric_difference, [-2, 3, 11, 13, 16, 24]);
+
+        let mut union: Vec<_> = a.union(&b).cloned().collect();
+        union.sort_unstable();
+        assert_eq!(union, [-2, 1, 3, 5, 9, 11, 13, 16, 19, 24]);
+    }
+
+    #[test]
+    fn test_set_operations_with_hash_set() {
+        let a = AHashSet::from([1, 3, 5]);
+        let b: HashSet<i32, crate::RandomState> = [3, 5, 7].iter().cloned().collect();
+
+        let mut intersection: Vec<_> = a.intersection(&b).cloned().collect();
+        intersection.sort_unstable();
+        assert_eq!(intersection, [3, 5]);
+        assert_eq!(a.difference(&b).collect::<Vec<_>>(), [&1]);
+        assert_eq!(a.symmetric_difference(&b).count(), 2);
+        assert_eq!(a.union(&b).count(), 4);
+        assert!(!a.is_disjoint(&b));
+        assert!(!a.is_subset(&b));
+        assert!(!a.is_superset(&b));
+    }
+
+    #[test]
+    fn test_retain_drain_clear() {
+        let mut s: AHashSet<i32> = (0..100).collect();
+        s.retain(|&x| x % 2 == 0);
+        assert_eq!(s.len(), 50);
+        assert!(s.contains(&2) && !s.contains(&3));
+        let capacity = s.capacity();
+        let mut drained: Vec<_> = s.drain().collect();
+        drained.sort_unstable();
+        assert_eq!(drained, (0..100).step_by(2).collect::<Vec<_>>());
+        assert!(s.is_empty());
+        assert!(s.capacity() >= capacity);
+        s.insert(1);
+        s.clear();
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn test_reserve_shrink_to_fit() {
+        let mut s: AHashSet<i32> = AHashSet::new();
+        s.reserve(100);
+        assert!(s.capacity() >= 100);
+        assert!(s.try_reserve(usize::MAX).is_err());
+        s.extend(0..10);
+        s.shrink_to(20);
+        assert!(s.capacity() >= 20);
+        s.shrink_to_fit();
+        assert!(s.capacity() >= 10);
+        assert_eq!(s.len(), 10);
+    }
+
+    #[test]
+    fn test_conversions() {
+        let std_set: HashSet<i32, crate::RandomState> = (1..4).collect();
+        let set: AHashSet<i32> = std_set.clone().into();
+        assert_eq!(set, AHashSet::from([1, 2, 3]));
+        let back: HashSet<i32, crate::RandomState> = set.into();
+        assert_eq!(back, std_set);
+    }
+}
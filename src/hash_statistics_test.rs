@@ -0,0 +1,418 @@
+//! Statistical tests modeled on those in SMHasher, so that the quality of a hasher can be checked without building
+//! and patching SMHasher itself.
+//!
+//! Each test measures the worst bias it finds, prints it along with the limit it is checked against, and fails if the
+//! limit is exceeded. The limits are derived from the number of samples so that an ideal hash essentially never
+//! fails. (Run with `--nocapture` to see the numbers.)
+use core::hash::Hasher;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// The worst result of one test, along with the largest value that is accepted.
+pub(crate) struct Report {
+    pub(crate) test: String,
+    pub(crate) measure: &'static str,
+    pub(crate) worst: f64,
+    pub(crate) limit: f64,
+}
+
+impl Report {
+    pub(crate) fn check(self) {
+        println!(
+            "{:<45} worst {} is {:.6} (limit {:.6})",
+            self.test, self.measure, self.worst, self.limit
+        );
+        assert!(
+            self.worst <= self.limit,
+            "{}: worst {} of {} exceeds {}",
+            self.test,
+            self.measure,
+            self.worst,
+            self.limit
+        );
+    }
+}
+
+fn hash_bytes<T: Hasher>(hasher: &impl Fn() -> T, bytes: &[u8]) -> u64 {
+    let mut hasher = hasher();
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+fn hash_u64<T: Hasher>(hasher: &impl Fn() -> T, bytes: &[u8]) -> u64 {
+    let mut value = [0; 8];
+    value.copy_from_slice(bytes);
+    let mut hasher = hasher();
+    hasher.write_u64(u64::from_le_bytes(value));
+    hasher.finish()
+}
+
+/// The largest bias (`|2p - 1|`) expected among `cells` proportions each measured over `samples` fair coin flips,
+/// with a generous margin.
+pub(crate) fn bias_limit(samples: usize, cells: usize) -> f64 {
+    let sigmas = (2.0 * (cells as f64).ln()).sqrt() + 2.5;
+    sigmas / (samples as f64).sqrt()
+}
+
+pub(crate) fn bias(count: u32, samples: usize) -> f64 {
+    (2.0 * count as f64 / samples as f64 - 1.0).abs()
+}
+
+/// Strict avalanche criterion: flipping any single input bit should flip each output bit with probability 1/2.
+/// Measures the probability for every input bit / output bit pair.
+fn avalanche(test: String, len: usize, samples: usize, hash: impl Fn(&[u8]) -> u64) -> Report {
+    let bits = len * 8;
+    let mut rng = StdRng::seed_from_u64(len as u64);
+    let mut counts = vec![0_u32; bits * 64];
+    let mut key = vec![0_u8; len];
+    for _ in 0..samples {
+        rng.fill(&mut key[..]);
+        let base = hash(&key);
+        for bit in 0..bits {
+            key[bit / 8] ^= 1 << (bit % 8);
+            let diff = base ^ hash(&key);
+            key[bit / 8] ^= 1 << (bit % 8);
+            for (out, count) in counts[bit * 64..(bit + 1) * 64].iter_mut().enumerate() {
+                *count += ((diff >> out) & 1) as u32;
+            }
+        }
+    }
+    Report {
+        test,
+        measure: "bias",
+        worst: counts.iter().map(|c| bias(*c, samples)).fold(0.0, f64::max),
+        limit: bias_limit(samples, counts.len()),
+    }
+}
+
+/// Bit independence criterion: when an input bit is flipped, whether one output bit flips should be independent of
+/// whether any other output bit flips. Measures the correlation of every pair of output bits for each input bit.
+fn bit_independence(test: String, len: usize, samples: usize, hash: impl Fn(&[u8]) -> u64) -> Report {
+    let bits = len * 8;
+    let mut rng = StdRng::seed_from_u64(len as u64 + 1);
+    let mut counts = vec![0_u32; bits * 64 * 64];
+    let mut key = vec![0_u8; len];
+    for _ in 0..samples {
+        rng.fill(&mut key[..]);
+        let base = hash(&key);
+        for bit in 0..bits {
+            key[bit / 8] ^= 1 << (bit % 8);
+            let diff = base ^ hash(&key);
+            key[bit / 8] ^= 1 << (bit % 8);
+            for j in 0..64 {
+                // The bits which flipped differently than bit `j`.
+                let differs = diff ^ (((diff >> j) & 1).wrapping_neg());
+                let row = &mut counts[(bit * 64 + j) * 64..(bit * 64 + j + 1) * 64];
+                for (k, count) in row.iter_mut().enumerate().skip(j + 1) {
+                    *count += ((differs >> k) & 1) as u32;
+                }
+            }
+        }
+    }
+    let mut worst = 0.0_f64;
+    for bit in 0..bits {
+        for j in 0..64 {
+            for k in j + 1..64 {
+                worst = worst.max(bias(counts[(bit * 64 + j) * 64 + k], samples));
+            }
+        }
+    }
+    Report {
+        test,
+        measure: "bias",
+        worst,
+        limit: bias_limit(samples, bits * 64 * 63 / 2),
+    }
+}
+
+/// Checks a set of hashes of distinct keys for collisions and for uneven distribution.
+///
+/// Full 64 bit collisions are not expected at all. Collisions in the upper and lower 32 bits are compared to the
+/// number expected for random values. The distribution is checked by counting how many hashes fall in each bucket of
+/// a window of bits at every offset, and comparing the counts to uniform with a chi-squared test.
+fn collisions_and_distribution(test: &str, mut hashes: Vec<u64>) -> Vec<Report> {
+    let n = hashes.len() as f64;
+    let mut reports = Vec::new();
+
+    hashes.sort_unstable();
+    let full = hashes.windows(2).filter(|w| w[0] == w[1]).count();
+    reports.push(Report {
+        test: format!("{} full collisions", test),
+        measure: "count",
+        worst: full as f64,
+        limit: 0.0,
+    });
+
+    let expected = n * (n - 1.0) / 2.0 / 2_f64.powi(32);
+    let mut worst_ratio = 0.0_f64;
+    for shift in [0, 32].iter() {
+        let mut truncated: Vec<u32> = hashes.iter().map(|h| (h >> shift) as u32).collect();
+        truncated.sort_unstable();
+        let collisions = truncated.windows(2).filter(|w| w[0] == w[1]).count() as f64;
+        worst_ratio = worst_ratio.max(collisions / expected);
+    }
+    reports.push(Report {
+        test: format!("{} 32 bit collisions", test),
+        measure: "ratio to expected",
+        worst: worst_ratio,
+        // Collisions are roughly Poisson distributed.
+        limit: (expected + 6.0 * expected.sqrt() + 6.0) / expected,
+    });
+
+    let window = ((n / 8.0).log2().floor() as u32).clamp(4, 16);
+    let buckets = 1_usize << window;
+    let df = (buckets - 1) as f64;
+    let expected = n / buckets as f64;
+    let mut worst_z = 0.0_f64;
+    let mut counts = vec![0_u32; buckets];
+    for offset in 0..64 {
+        counts.iter_mut().for_each(|c| *c = 0);
+        for hash in hashes.iter() {
+            counts[(hash.rotate_right(offset) as usize) & (buckets - 1)] += 1;
+        }
+        let chi_squared: f64 = counts.iter().map(|c| (*c as f64 - expected).powi(2) / expected).sum();
+        worst_z = worst_z.max((chi_squared - df) / (2.0 * df).sqrt());
+    }
+    reports.push(Report {
+        test: format!("{} distribution ({} bit window)", test, window),
+        measure: "chi-squared z-score",
+        worst: worst_z,
+        limit: 6.0,
+    });
+    reports
+}
+
+fn check_all(reports: Vec<Report>) {
+    for report in reports {
+        report.check();
+    }
+}
+
+/// Calls `f` with every key of `len` bytes which has at most `max_bits` bits set.
+fn sparse_keys(len: usize, max_bits: usize, f: &mut impl FnMut(&[u8])) {
+    fn recurse(key: &mut [u8], start: usize, remaining: usize, f: &mut impl FnMut(&[u8])) {
+        f(key);
+        if remaining == 0 {
+            return;
+        }
+        for bit in start..key.len() * 8 {
+            key[bit / 8] ^= 1 << (bit % 8);
+            recurse(key, bit + 1, remaining - 1, f);
+            key[bit / 8] ^= 1 << (bit % 8);
+        }
+    }
+    recurse(&mut vec![0; len], 0, max_bits, f);
+}
+
+/// Calls `f` with every ordering of `blocks`.
+fn permutations(blocks: &mut [u32], fixed: usize, f: &mut impl FnMut(&[u32])) {
+    if fixed == blocks.len() {
+        f(blocks);
+        return;
+    }
+    for i in fixed..blocks.len() {
+        blocks.swap(fixed, i);
+        permutations(blocks, fixed + 1, f);
+        blocks.swap(fixed, i);
+    }
+}
+
+fn test_avalanche<T: Hasher>(hasher: impl Fn() -> T) {
+    // Covers each of the length ranges which are handled differently by the hashers. (Shorter keys have too few
+    // possible values to sample randomly.)
+    for len in [3, 4, 7, 8, 12, 16, 24, 32, 48, 64, 100, 128].iter() {
+        let samples = if *len <= 32 { 4000 } else { 1000 };
+        avalanche(format!("avalanche {} bytes", len), *len, samples, |k| hash_bytes(&hasher, k)).check();
+    }
+    avalanche("avalanche write_u64".into(), 8, 20000, |k| hash_u64(&hasher, k)).check();
+}
+
+fn test_bit_independence<T: Hasher>(hasher: impl Fn() -> T) {
+    bit_independence("bit independence 4 bytes".into(), 4, 2000, |k| hash_bytes(&hasher, k)).check();
+    bit_independence("bit independence 16 bytes".into(), 16, 2000, |k| hash_bytes(&hasher, k)).check();
+    bit_independence("bit independence write_u64".into(), 8, 2000, |k| hash_u64(&hasher, k)).check();
+}
+
+fn test_sparse_keys<T: Hasher>(hasher: impl Fn() -> T) {
+    for (len, max_bits) in [(4, 4), (8, 3), (16, 2), (32, 2), (64, 2), (128, 2)].iter() {
+        let mut hashes = Vec::new();
+        sparse_keys(*len, *max_bits, &mut |key| hashes.push(hash_bytes(&hasher, key)));
+        let test = format!("sparse {} bytes with up to {} bits set", len, max_bits);
+        check_all(collisions_and_distribution(&test, hashes));
+    }
+    let mut hashes = Vec::new();
+    sparse_keys(8, 3, &mut |key| hashes.push(hash_u64(&hasher, key)));
+    check_all(collisions_and_distribution("sparse write_u64 with up to 3 bits set", hashes));
+}
+
+/// Keys which consist of a random block repeated several times.
+fn test_cyclic_keys<T: Hasher>(hasher: impl Fn() -> T) {
+    let mut rng = StdRng::seed_from_u64(0xC1C1E);
+    for (cycle, repeats) in [(3, 8), (4, 8), (8, 4), (8, 8), (12, 8), (16, 4), (16, 8)].iter() {
+        let mut blocks = std::collections::HashSet::new();
+        while blocks.len() < 100_000 {
+            let mut block = vec![0_u8; *cycle];
+            rng.fill(&mut block[..]);
+            blocks.insert(block);
+        }
+        let hashes = blocks.iter().map(|block| hash_bytes(&hasher, &block.repeat(*repeats))).collect();
+        let test = format!("cyclic {} bytes repeated {} times", cycle, repeats);
+        check_all(collisions_and_distribution(&test, hashes));
+    }
+}
+
+/// Keys which are all of the orderings of a set of 4 byte blocks.
+fn test_permutation_keys<T: Hasher>(hasher: impl Fn() -> T) {
+    let block_sets: [(&str, [u32; 8]); 4] = [
+        ("low bits", [0, 1, 2, 3, 4, 5, 6, 7]),
+        ("high bits", [0, 1 << 29, 2 << 29, 3 << 29, 4 << 29, 5 << 29, 6 << 29, 7 << 29]),
+        ("single bits", [1, 1 << 4, 1 << 8, 1 << 12, 1 << 16, 1 << 20, 1 << 24, 1 << 28]),
+        ("repeated bytes", [0, 0x11111111, 0x22222222, 0x33333333, 0x44444444, 0x55555555, 0x66666666, 0x77777777]),
+    ];
+    for (name, blocks) in block_sets.iter() {
+        let mut blocks = *blocks;
+        let mut hashes = Vec::new();
+        permutations(&mut blocks, 0, &mut |perm| {
+            let key: Vec<u8> = perm.iter().flat_map(|b| b.to_le_bytes().to_vec()).collect();
+            hashes.push(hash_bytes(&hasher, &key));
+        });
+        check_all(collisions_and_distribution(&format!("permutations of {}", name), hashes));
+    }
+}
+
+/// For every difference of one or two bits between inputs, checks that the difference between the outputs is not
+/// zero and does not repeat across random keys. (A repeated output difference is a differential which an attacker
+/// could use to construct collisions.)
+fn differential(test: String, len: usize, keys: usize, hash: impl Fn(&[u8]) -> u64) -> Report {
+    let mut rng = StdRng::seed_from_u64(len as u64 + 2);
+    let bits = len * 8;
+    let mut key = vec![0_u8; len];
+    let mut repeats = 0;
+    let mut diffs = Vec::with_capacity(keys);
+    for first in 0..bits {
+        for second in first..bits {
+            diffs.clear();
+            for _ in 0..keys {
+                rng.fill(&mut key[..]);
+                let base = hash(&key);
+                key[first / 8] ^= 1 << (first % 8);
+                if second != first {
+                    key[second / 8] ^= 1 << (second % 8);
+                }
+                let diff = base ^ hash(&key);
+                if diff == 0 {
+                    repeats += 1;
+                }
+                diffs.push(diff);
+            }
+            diffs.sort_unstable();
+            repeats += diffs.windows(2).filter(|w| w[0] == w[1]).count();
+        }
+    }
+    Report {
+        test,
+        measure: "repeated differentials",
+        worst: repeats as f64,
+        limit: 0.0,
+    }
+}
+
+fn test_differentials<T: Hasher>(hasher: impl Fn() -> T) {
+    differential("differential 4 bytes".into(), 4, 200, |k| hash_bytes(&hasher, k)).check();
+    differential("differential 8 bytes".into(), 8, 100, |k| hash_bytes(&hasher, k)).check();
+    differential("differential 16 bytes".into(), 16, 30, |k| hash_bytes(&hasher, k)).check();
+    differential("differential 32 bytes".into(), 32, 10, |k| hash_bytes(&hasher, k)).check();
+    differential("differential write_u64".into(), 8, 100, |k| hash_u64(&hasher, k)).check();
+}
+
+/// Checks that the keys affect the output as well as the input does: flipping any bit of the keys should flip each
+/// output bit with probability 1/2, and sparse keys should produce well distributed outputs for a fixed input.
+fn test_seed_independence<T: Hasher>(constructor: impl Fn(u64, u64) -> T) {
+    for len in [0, 4, 8, 16, 64].iter() {
+        let mut rng = StdRng::seed_from_u64(*len as u64 + 3);
+        let input: Vec<u8> = (0..*len).map(|_| rng.gen()).collect();
+        let hash = |seeds: &[u8]| {
+            let mut k1 = [0; 8];
+            let mut k2 = [0; 8];
+            k1.copy_from_slice(&seeds[..8]);
+            k2.copy_from_slice(&seeds[8..]);
+            let mut hasher = constructor(u64::from_le_bytes(k1), u64::from_le_bytes(k2));
+            hasher.write(&input);
+            hasher.finish()
+        };
+        avalanche(format!("seed avalanche {} byte input", len), 16, 2000, hash).check();
+
+        let mut hashes = Vec::new();
+        sparse_keys(16, 2, &mut |seeds| hashes.push(hash(seeds)));
+        let test = format!("sparse seeds {} byte input", len);
+        check_all(collisions_and_distribution(&test, hashes));
+    }
+}
+
+/// Generates a `fallback_tests` and an `aes_tests` module containing the given tests, in which `AHasher` is that
+/// backend's hasher and `WEAK_KEY` is a key which is a special case for it. (If AES-NI is not enabled, the `aes_tests`
+/// run against the software implementation of AES, or with `runtime-dispatch` are skipped if the CPU lacks AES-NI)
+macro_rules! test_both_backends {
+    ($($(#[$attr:meta])* fn $name:ident() $body:block)*) => {
+        mod fallback_tests {
+            use super::*;
+            use crate::fallback_hash::AHasher;
+
+            #[allow(dead_code)]
+            const WEAK_KEY: u64 = 0;
+
+            $(#[test] $(#[$attr])* fn $name() $body)*
+        }
+
+        mod aes_tests {
+            use super::*;
+            use crate::aes_hash::AHasher;
+
+            #[allow(dead_code)]
+            const WEAK_KEY: u64 = 0x5252_5252_5252_5252; //This encrypts to 0.
+
+            $(
+                #[test]
+                $(#[$attr])*
+                fn $name() {
+                    if crate::operations::aes_unavailable() {
+                        return;
+                    }
+                    $body
+                }
+            )*
+        }
+    };
+}
+pub(crate) use test_both_backends;
+
+test_both_backends! {
+    fn avalanche() {
+        test_avalanche(|| AHasher::test_with_keys(WEAK_KEY, WEAK_KEY));
+        test_avalanche(|| AHasher::test_with_keys(12345, 67890));
+    }
+
+    fn bit_independence() {
+        test_bit_independence(|| AHasher::test_with_keys(12345, 67890));
+    }
+
+    fn sparse_keys() {
+        test_sparse_keys(|| AHasher::test_with_keys(12345, 67890));
+    }
+
+    fn cyclic_keys() {
+        test_cyclic_keys(|| AHasher::test_with_keys(12345, 67890));
+    }
+
+    fn permutation_keys() {
+        test_permutation_keys(|| AHasher::test_with_keys(12345, 67890));
+    }
+
+    fn differentials() {
+        test_differentials(|| AHasher::test_with_keys(12345, 67890));
+    }
+
+    fn seed_independence() {
+        test_seed_independence(AHasher::test_with_keys);
+    }
+}
@@ -0,0 +1,290 @@
+use core::cmp::Ordering;
+use core::fmt::{self, Debug};
+use core::hash::{BuildHasher, Hash, Hasher};
+use core::ops::Deref;
+
+/// The value hashed by a state to obtain its fingerprint. (Used to check that [Hashed] values are only used with the
+/// state that produced them)
+#[cfg(debug_assertions)]
+const FINGERPRINT_INPUT: u64 = 0x5f3c_99e1_0a47_d2b6;
+
+#[cfg(debug_assertions)]
+#[inline]
+fn fingerprint<S: BuildHasher>(state: &S) -> u64 {
+    state.hash_one(FINGERPRINT_INPUT)
+}
+
+/// A key along with its hash, computed once when it is created.
+///
+/// When the same key is looked up in several maps, each map normally hashes it again. Instead the key can be wrapped
+/// in `Hashed`, and the maps created with a [PassThroughState] which uses the stored hash directly. So the key is
+/// hashed only once no matter how many lookups are performed.
+///
+/// All of the `Hashed` values used with a map must be created with the same state as the map's [PassThroughState].
+/// Otherwise equal keys will have different hashes and lookups will fail. In debug builds this is checked and
+/// mismatches cause a panic.
+///
+/// `Hashed` values compare equal if their keys do, and dereference to the key.
+///
+/// # Example
+///
+/// ```
+/// use ahash::{AHashMap, Hashed, PassThroughState, RandomState};
+/// use std::hash::BuildHasher;
+///
+/// type Map<'a> = AHashMap<Hashed<&'a str>, u32, PassThroughState>;
+///
+/// let state = RandomState::new();
+/// let mut names = Map::with_hasher(PassThroughState::new(&state));
+/// let mut ages = Map::with_hasher(PassThroughState::new(&state));
+///
+/// let key = Hashed::new("alice", &state);
+/// names.insert(key, 1);
+/// ages.insert(key, 30);
+/// assert_eq!(Some(&1), names.get(&key));
+/// assert_eq!(Some(&30), ages.get(&key));
+/// assert_eq!(BuildHasher::hash_one(&state, "alice"), key.hash_value());
+/// ```
+#[derive(Clone, Copy)]
+pub struct Hashed<K> {
+    hash: u64,
+    /// The fingerprint of the state that computed `hash`.
+    #[cfg(debug_assertions)]
+    state: u64,
+    key: K,
+}
+
+impl<K: Hash> Hashed<K> {
+    /// Hashes `key` with `state` and stores the result along with it.
+    ///
+    /// The key is hashed via its `Hash` implementation, as [BuildHasher::hash_one] does.
+    #[inline]
+    pub fn new<S: BuildHasher>(key: K, state: &S) -> Self {
+        Hashed {
+            hash: state.hash_one(&key),
+            #[cfg(debug_assertions)]
+            state: fingerprint(state),
+            key,
+        }
+    }
+}
+
+impl<K> Hashed<K> {
+    /// Returns the stored hash of the key.
+    #[inline]
+    pub fn hash_value(&self) -> u64 {
+        self.hash
+    }
+
+    /// Returns a reference to the key.
+    #[inline]
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Returns the key, discarding the hash.
+    #[inline]
+    pub fn into_inner(self) -> K {
+        self.key
+    }
+}
+
+impl<K> Deref for Hashed<K> {
+    type Target = K;
+
+    #[inline]
+    fn deref(&self) -> &K {
+        &self.key
+    }
+}
+
+/// Writes only the stored hash (so it can be passed through by an [IdentityHasher]).
+impl<K> Hash for Hashed<K> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        #[cfg(debug_assertions)]
+        state.write_u128((self.state as u128) << 64 | self.hash as u128);
+        #[cfg(not(debug_assertions))]
+        state.write_u64(self.hash);
+    }
+}
+
+impl<K: PartialEq> PartialEq for Hashed<K> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.key == other.key
+    }
+}
+
+impl<K: Eq> Eq for Hashed<K> {}
+
+impl<K: PartialOrd> PartialOrd for Hashed<K> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.key.partial_cmp(&other.key)
+    }
+}
+
+impl<K: Ord> Ord for Hashed<K> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+impl<K: Debug> Debug for Hashed<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hashed")
+            .field("hash", &self.hash)
+            .field("key", &self.key)
+            .finish()
+    }
+}
+
+/// A [BuildHasher] for maps whose keys are [Hashed], which uses their stored hash rather than hashing them again.
+///
+/// It is created from the state used to hash the keys. In debug builds, using it with a [Hashed] value created by a
+/// different state panics.
+///
+/// See [Hashed] for an example.
+#[derive(Clone)]
+pub struct PassThroughState {
+    /// The fingerprint of the state used to create the keys.
+    #[cfg(debug_assertions)]
+    state: u64,
+}
+
+impl PassThroughState {
+    /// Creates a `PassThroughState` for keys which are hashed with `state`.
+    #[inline]
+    #[allow(unused_variables)] // Is only used in debug builds
+    pub fn new<S: BuildHasher>(state: &S) -> Self {
+        PassThroughState {
+            #[cfg(debug_assertions)]
+            state: fingerprint(state),
+        }
+    }
+}
+
+impl BuildHasher for PassThroughState {
+    type Hasher = IdentityHasher;
+
+    #[inline]
+    fn build_hasher(&self) -> IdentityHasher {
+        IdentityHasher {
+            hash: 0,
+            #[cfg(debug_assertions)]
+            state: self.state,
+        }
+    }
+}
+
+impl Debug for PassThroughState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PassThroughState").finish_non_exhaustive()
+    }
+}
+
+/// The [Hasher] created by [PassThroughState]. Its result is the hash stored in the [Hashed] value written to it.
+///
+/// It also passes through a `u64` written with `write_u64` unchanged, but panics if it is used to hash anything else.
+#[derive(Clone)]
+pub struct IdentityHasher {
+    hash: u64,
+    #[cfg(debug_assertions)]
+    state: u64,
+}
+
+impl Hasher for IdentityHasher {
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+
+    fn write(&mut self, _bytes: &[u8]) {
+        panic!("IdentityHasher can only be used to hash `Hashed` values");
+    }
+
+    #[inline]
+    fn write_u64(&mut self, i: u64) {
+        self.hash = i;
+    }
+
+    /// `Hashed` values write their hash along with the fingerprint of their state in debug builds.
+    #[cfg(debug_assertions)]
+    #[inline]
+    fn write_u128(&mut self, i: u128) {
+        assert_eq!(
+            self.state,
+            (i >> 64) as u64,
+            "A `Hashed` value was used with a different state than the one which created it"
+        );
+        self.hash = i as u64;
+    }
+}
+
+impl Debug for IdentityHasher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdentityHasher")
+            .field("hash", &self.hash)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::RandomState;
+
+    #[test]
+    fn test_hash_is_passed_through() {
+        let state = RandomState::with_seeds(1, 2);
+        let key = Hashed::new("key", &state);
+        assert_eq!(BuildHasher::hash_one(&state, "key"), key.hash_value());
+        assert_eq!(key.hash_value(), PassThroughState::new(&state).hash_one(key));
+        assert_eq!(5, PassThroughState::new(&state).hash_one(5_u64));
+        assert_eq!("key", *key);
+        assert_eq!("key", key.into_inner());
+    }
+
+    #[test]
+    fn test_equality() {
+        let state = RandomState::with_seeds(1, 2);
+        assert_eq!(Hashed::new(1, &state), Hashed::new(1, &state));
+        assert_ne!(Hashed::new(1, &state), Hashed::new(2, &state));
+        assert!(Hashed::new(1, &state) < Hashed::new(2, &state));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_shared_between_maps() {
+        use crate::AHashMap;
+
+        let state = RandomState::new();
+        let keys: Vec<_> = (0..1000).map(|i| Hashed::new(i.to_string(), &state)).collect();
+        let mut a: AHashMap<_, _, _> = AHashMap::with_hasher(PassThroughState::new(&state));
+        let mut b: AHashMap<_, _, _> = AHashMap::with_hasher(PassThroughState::new(&state));
+        for (i, key) in keys.iter().enumerate() {
+            a.insert(key.clone(), i);
+            b.insert(key.clone(), i * 2);
+        }
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(Some(&i), a.get(key));
+            assert_eq!(Some(&(i * 2)), b.get(&Hashed::new(key.to_string(), &state)));
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "different state")]
+    fn test_mismatched_state_panics() {
+        let key = Hashed::new(1, &RandomState::with_seeds(1, 2));
+        PassThroughState::new(&RandomState::with_seeds(3, 4)).hash_one(key);
+    }
+
+    #[test]
+    #[should_panic(expected = "can only be used")]
+    fn test_other_keys_panic() {
+        PassThroughState::new(&RandomState::with_seeds(1, 2)).hash_one("not hashed");
+    }
+}
@@ -0,0 +1,859 @@
+//! `AHashIndexMap`, a hash map which iterates in insertion order, and the types its methods return.
+
+use crate::table::RawTable;
+use alloc::vec::{self, Vec};
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt::{self, Debug};
+use core::hash::{BuildHasher, Hash};
+use core::iter::{FromIterator, FusedIterator};
+use core::mem;
+use core::ops::Index;
+use core::slice;
+
+#[derive(Clone)]
+struct Bucket<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+}
+
+/// A hash map which keeps its entries in the order they were inserted, using [`RandomState`](crate::RandomState) to
+/// hash the keys.
+///
+/// The entries are stored in a `Vec`, and a hash table keyed by aHash maps each key to its position in it. So lookups
+/// take constant time like a normal hash map, while iteration visits the entries in a deterministic order which does
+/// not depend on the hasher or its keys. This makes it suitable for producing reproducible output.
+///
+/// Inserting a key which is already present updates the value but does not change its position. Entries can also be
+/// accessed by their position with [`get_index`](AHashIndexMap::get_index). Removing an entry can be done either by
+/// [`swap_remove`](AHashIndexMap::swap_remove), which is constant time but moves the last entry into the gap, or
+/// [`shift_remove`](AHashIndexMap::shift_remove), which preserves the order of the other entries but takes linear
+/// time.
+///
+/// Requires either the `std` or `alloc` feature to be enabled.
+///
+/// # Example
+///
+/// ```
+/// use ahash::AHashIndexMap;
+///
+/// let mut map: AHashIndexMap<_, _> = AHashIndexMap::new();
+/// map.insert("z", 1);
+/// map.insert("a", 2);
+/// map.insert("m", 3);
+/// assert_eq!(vec!["z", "a", "m"], map.keys().copied().collect::<Vec<_>>());
+///
+/// map.shift_remove("z");
+/// assert_eq!(Some((&"a", &2)), map.get_index(0));
+///
+/// map.sort_keys();
+/// map.insert("b", 4);
+/// assert_eq!(vec!["a", "m", "b"], map.keys().copied().collect::<Vec<_>>());
+/// ```
+#[derive(Clone)]
+pub struct AHashIndexMap<K, V, S = crate::RandomState> {
+    entries: Vec<Bucket<K, V>>,
+    /// The position in `entries` of each key.
+    indices: RawTable<usize>,
+    hash_builder: S,
+}
+
+impl<K, V, S> AHashIndexMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    pub fn new() -> Self {
+        AHashIndexMap::with_hasher(S::default())
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        AHashIndexMap::with_capacity_and_hasher(capacity, S::default())
+    }
+}
+
+impl<K, V, S> AHashIndexMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    pub fn with_hasher(hash_builder: S) -> Self {
+        AHashIndexMap {
+            entries: Vec::new(),
+            indices: RawTable::new(),
+            hash_builder,
+        }
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        AHashIndexMap {
+            entries: Vec::with_capacity(capacity),
+            indices: RawTable::with_capacity(capacity),
+            hash_builder,
+        }
+    }
+
+    /// Returns the slot in `indices` which holds the position of `k`.
+    #[inline]
+    fn find<Q>(&self, hash: u64, k: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let entries = &self.entries;
+        self.indices.find(hash, |&i| entries[i].key.borrow() == k)
+    }
+
+    /// Reserves capacity for at least `additional` more elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new allocation size overflows `usize`.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.indices.reserve(additional);
+        self.entries.reserve(additional);
+    }
+
+    /// Shrinks the capacity of the map as much as possible.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.indices.shrink_to(0);
+        self.entries.shrink_to_fit();
+    }
+
+    /// Inserts a key-value pair into the map.
+    ///
+    /// If the map did not have this key present, it is added at the end and `None` is returned. Otherwise the value
+    /// is updated and the old value is returned. The key and its position are not updated.
+    #[inline]
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        self.insert_full(k, v).1
+    }
+
+    /// Inserts a key-value pair into the map, and returns the position of the entry along with the old value (if any).
+    pub fn insert_full(&mut self, k: K, v: V) -> (usize, Option<V>) {
+        let hash = self.hash_builder.hash_one(&k);
+        match self.find(hash, &k) {
+            Some(slot) => {
+                let index = *self.indices.get(slot);
+                (index, Some(mem::replace(&mut self.entries[index].value, v)))
+            }
+            None => {
+                let index = self.entries.len();
+                self.indices.insert(hash, index);
+                self.entries.push(Bucket { hash, key: k, value: v });
+                (index, None)
+            }
+        }
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    #[inline]
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_full(k).map(|(_, _, v)| v)
+    }
+
+    /// Returns the key-value pair corresponding to the supplied key.
+    #[inline]
+    pub fn get_key_value<Q>(&self, k: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_full(k).map(|(_, k, v)| (k, v))
+    }
+
+    /// Returns the position of the key along with the key-value pair.
+    #[inline]
+    pub fn get_full<Q>(&self, k: &Q) -> Option<(usize, &K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.get_index_of(k)?;
+        let bucket = &self.entries[index];
+        Some((index, &bucket.key, &bucket.value))
+    }
+
+    /// Returns the position of the key in the map.
+    #[inline]
+    pub fn get_index_of<Q>(&self, k: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = self.find(self.hash_builder.hash_one(k), k)?;
+        Some(*self.indices.get(slot))
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    #[inline]
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_index_of(k).is_some()
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    #[inline]
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.get_index_of(k)?;
+        Some(&mut self.entries[index].value)
+    }
+
+    /// Removes a key from the map, returning its value, by replacing it with the last entry.
+    ///
+    /// This takes constant time, but changes the position of the last entry.
+    #[inline]
+    pub fn swap_remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.swap_remove_full(k).map(|(_, _, v)| v)
+    }
+
+    /// Removes a key from the map by replacing it with the last entry, and returns its position, key and value.
+    pub fn swap_remove_full<Q>(&mut self, k: &Q) -> Option<(usize, K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = self.find(self.hash_builder.hash_one(k), k)?;
+        let index = self.indices.remove(slot);
+        let (key, value) = self.swap_remove_entry(index);
+        Some((index, key, value))
+    }
+
+    /// Removes a key from the map, returning its value, by shifting all of the entries that follow it.
+    ///
+    /// This preserves the order of the remaining entries, but takes linear time.
+    #[inline]
+    pub fn shift_remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.shift_remove_full(k).map(|(_, _, v)| v)
+    }
+
+    /// Removes a key from the map by shifting all of the entries that follow it, and returns its position, key and
+    /// value.
+    pub fn shift_remove_full<Q>(&mut self, k: &Q) -> Option<(usize, K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = self.find(self.hash_builder.hash_one(k), k)?;
+        let index = self.indices.remove(slot);
+        let (key, value) = self.shift_remove_entry(index);
+        Some((index, key, value))
+    }
+}
+
+impl<K, V, S> AHashIndexMap<K, V, S> {
+    /// Returns the number of elements the map can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.entries.capacity().min(self.indices.capacity())
+    }
+
+    /// Returns the number of elements in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the map contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes all the entries, keeping the allocated memory for reuse.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.indices.clear();
+    }
+
+    /// Returns a reference to the map's [`BuildHasher`].
+    #[inline]
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Returns the key-value pair at the given position.
+    #[inline]
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.entries.get(index).map(|b| (&b.key, &b.value))
+    }
+
+    /// Returns the key and a mutable reference to the value at the given position.
+    #[inline]
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        self.entries.get_mut(index).map(|b| (&b.key, &mut b.value))
+    }
+
+    /// Returns the first key-value pair.
+    #[inline]
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.get_index(0)
+    }
+
+    /// Returns the last key-value pair.
+    #[inline]
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.get_index(self.len().wrapping_sub(1))
+    }
+
+    /// Removes the entry at the given position by replacing it with the last entry, and returns it.
+    pub fn swap_remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        let hash = self.entries.get(index)?.hash;
+        let slot = self.indices.find(hash, |&i| i == index).expect("Missing index");
+        self.indices.remove(slot);
+        Some(self.swap_remove_entry(index))
+    }
+
+    /// Removes the entry at the given position by shifting all of the entries that follow it, and returns it.
+    pub fn shift_remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        let hash = self.entries.get(index)?.hash;
+        let slot = self.indices.find(hash, |&i| i == index).expect("Missing index");
+        self.indices.remove(slot);
+        Some(self.shift_remove_entry(index))
+    }
+
+    /// Removes the last entry and returns it.
+    #[inline]
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        self.swap_remove_index(self.len().wrapping_sub(1))
+    }
+
+    /// Removes the entry at `index` (whose position has already been removed from `indices`) and moves the last entry
+    /// into its place.
+    fn swap_remove_entry(&mut self, index: usize) -> (K, V) {
+        let last = self.entries.len() - 1;
+        if index != last {
+            let slot = self
+                .indices
+                .find(self.entries[last].hash, |&i| i == last)
+                .expect("Missing index");
+            *self.indices.get_mut(slot) = index;
+        }
+        let bucket = self.entries.swap_remove(index);
+        (bucket.key, bucket.value)
+    }
+
+    /// Removes the entry at `index` (whose position has already been removed from `indices`) and shifts the following
+    /// entries back.
+    fn shift_remove_entry(&mut self, index: usize) -> (K, V) {
+        let bucket = self.entries.remove(index);
+        if index < self.entries.len() {
+            for i in self.indices.iter_mut() {
+                if *i > index {
+                    *i -= 1;
+                }
+            }
+        }
+        (bucket.key, bucket.value)
+    }
+
+    /// Retains only the elements specified by the predicate, keeping the order of the remaining entries.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let len = self.entries.len();
+        let guard = self.rebuild_indices_guard();
+        guard.entries.retain_mut(|b| f(&b.key, &mut b.value));
+        if guard.entries.len() == len {
+            mem::forget(guard);
+        }
+    }
+
+    /// Sorts the entries by their keys.
+    pub fn sort_keys(&mut self)
+    where
+        K: Ord,
+    {
+        self.sort_by(|k1, _, k2, _| k1.cmp(k2));
+    }
+
+    /// Sorts the entries using the provided comparison function. The sort is stable.
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&K, &V, &K, &V) -> Ordering,
+    {
+        let guard = self.rebuild_indices_guard();
+        guard.entries.sort_by(|a, b| compare(&a.key, &a.value, &b.key, &b.value));
+    }
+
+    /// Reverses the order of the entries.
+    pub fn reverse(&mut self) {
+        self.entries.reverse();
+        let last = self.entries.len().wrapping_sub(1);
+        for i in self.indices.iter_mut() {
+            *i = last - *i;
+        }
+    }
+
+    /// Returns a guard through which the entries can be rearranged, which rebuilds the indices when it is dropped.
+    /// (Including if the code rearranging them panics)
+    fn rebuild_indices_guard(&mut self) -> RebuildIndices<'_, K, V> {
+        RebuildIndices {
+            entries: &mut self.entries,
+            indices: &mut self.indices,
+        }
+    }
+
+    /// An iterator visiting all keys in order.
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys(self.entries.iter())
+    }
+
+    /// An iterator visiting all values in order.
+    #[inline]
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values(self.entries.iter())
+    }
+
+    /// An iterator visiting all values mutably in order.
+    #[inline]
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut(self.entries.iter_mut())
+    }
+
+    /// An iterator visiting all key-value pairs in order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter(self.entries.iter())
+    }
+
+    /// An iterator visiting all key-value pairs in order, with mutable references to the values.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut(self.entries.iter_mut())
+    }
+}
+
+struct RebuildIndices<'a, K, V> {
+    entries: &'a mut Vec<Bucket<K, V>>,
+    indices: &'a mut RawTable<usize>,
+}
+
+impl<K, V> Drop for RebuildIndices<'_, K, V> {
+    fn drop(&mut self) {
+        self.indices.clear();
+        for (index, bucket) in self.entries.iter().enumerate() {
+            self.indices.insert(bucket.hash, index);
+        }
+    }
+}
+
+impl<K, V, const N: usize> From<[(K, V); N]> for AHashIndexMap<K, V>
+where
+    K: Eq + Hash,
+{
+    fn from(arr: [(K, V); N]) -> Self {
+        AHashIndexMap::from_iter(arr)
+    }
+}
+
+/// Maps are equal if they contain the same key-value pairs, regardless of their order.
+impl<K, V, S> PartialEq for AHashIndexMap<K, V, S>
+where
+    K: Eq + Hash,
+    V: PartialEq,
+    S: BuildHasher,
+{
+    fn eq(&self, other: &AHashIndexMap<K, V, S>) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl<K, V, S> Eq for AHashIndexMap<K, V, S>
+where
+    K: Eq + Hash,
+    V: Eq,
+    S: BuildHasher,
+{
+}
+
+impl<K, Q: ?Sized, V, S> Index<&Q> for AHashIndexMap<K, V, S>
+where
+    K: Eq + Hash + Borrow<Q>,
+    Q: Eq + Hash,
+    S: BuildHasher,
+{
+    type Output = V;
+
+    /// Returns a reference to the value corresponding to the supplied key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not present in the map.
+    #[inline]
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("no entry found for key")
+    }
+}
+
+impl<K, V, S> Index<usize> for AHashIndexMap<K, V, S> {
+    type Output = V;
+
+    /// Returns a reference to the value at the supplied position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is out of bounds.
+    #[inline]
+    fn index(&self, index: usize) -> &V {
+        &self.entries[index].value
+    }
+}
+
+impl<K, V, S> Debug for AHashIndexMap<K, V, S>
+where
+    K: Debug,
+    V: Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for AHashIndexMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = AHashIndexMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a AHashIndexMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a mut AHashIndexMap<K, V, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V, S> IntoIterator for AHashIndexMap<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.entries.into_iter())
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for AHashIndexMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    #[inline]
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        iter.for_each(move |(k, v)| {
+            self.insert(k, v);
+        });
+    }
+}
+
+impl<'a, K, V, S> Extend<(&'a K, &'a V)> for AHashIndexMap<K, V, S>
+where
+    K: Eq + Hash + Copy + 'a,
+    V: Copy + 'a,
+    S: BuildHasher,
+{
+    #[inline]
+    fn extend<T: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: T) {
+        self.extend(iter.into_iter().map(|(&k, &v)| (k, v)))
+    }
+}
+
+impl<K, V, S> Default for AHashIndexMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    #[inline]
+    fn default() -> AHashIndexMap<K, V, S> {
+        AHashIndexMap::with_hasher(Default::default())
+    }
+}
+
+/// Defines a wrapper around an iterator over the entries which maps each `Bucket` to `$item`.
+macro_rules! entries_iter {
+    ($(#[$doc:meta])* $name:ident $(<$lifetime:lifetime>)?, $inner:ty, $item:ty, |$bucket:ident| $map:expr) => {
+        $(#[$doc])*
+        pub struct $name<$($lifetime,)? K, V>($inner);
+
+        impl<$($lifetime,)? K, V> Iterator for $name<$($lifetime,)? K, V> {
+            type Item = $item;
+
+            #[inline]
+            fn next(&mut self) -> Option<$item> {
+                self.0.next().map(|$bucket| $map)
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.0.size_hint()
+            }
+        }
+
+        impl<$($lifetime,)? K, V> DoubleEndedIterator for $name<$($lifetime,)? K, V> {
+            #[inline]
+            fn next_back(&mut self) -> Option<$item> {
+                self.0.next_back().map(|$bucket| $map)
+            }
+        }
+
+        impl<$($lifetime,)? K, V> ExactSizeIterator for $name<$($lifetime,)? K, V> {}
+
+        impl<$($lifetime,)? K, V> FusedIterator for $name<$($lifetime,)? K, V> {}
+    };
+}
+
+entries_iter!(
+    /// An iterator over the entries of an [`AHashIndexMap`] in order. Created by [`AHashIndexMap::iter`].
+    Iter<'a>, slice::Iter<'a, Bucket<K, V>>, (&'a K, &'a V), |b| (&b.key, &b.value)
+);
+entries_iter!(
+    /// A mutable iterator over the entries of an [`AHashIndexMap`] in order. Created by [`AHashIndexMap::iter_mut`].
+    IterMut<'a>, slice::IterMut<'a, Bucket<K, V>>, (&'a K, &'a mut V), |b| (&b.key, &mut b.value)
+);
+entries_iter!(
+    /// An owning iterator over the entries of an [`AHashIndexMap`] in order. Created by its `into_iter` method.
+    IntoIter, vec::IntoIter<Bucket<K, V>>, (K, V), |b| (b.key, b.value)
+);
+entries_iter!(
+    /// An iterator over the keys of an [`AHashIndexMap`] in order. Created by [`AHashIndexMap::keys`].
+    Keys<'a>, slice::Iter<'a, Bucket<K, V>>, &'a K, |b| &b.key
+);
+entries_iter!(
+    /// An iterator over the values of an [`AHashIndexMap`] in order. Created by [`AHashIndexMap::values`].
+    Values<'a>, slice::Iter<'a, Bucket<K, V>>, &'a V, |b| &b.value
+);
+entries_iter!(
+    /// A mutable iterator over the values of an [`AHashIndexMap`] in order. Created by
+    /// [`AHashIndexMap::values_mut`].
+    ValuesMut<'a>, slice::IterMut<'a, Bucket<K, V>>, &'a mut V, |b| &mut b.value
+);
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Iter(self.0.clone())
+    }
+}
+
+impl<K, V> Clone for Keys<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Keys(self.0.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::RandomState;
+
+    fn keys<K: Copy, V, S>(map: &AHashIndexMap<K, V, S>) -> Vec<K> {
+        map.keys().copied().collect()
+    }
+
+    /// Checks that every key is found at the position it is iterated at.
+    fn check_indices<K: Hash + Eq, V, S: BuildHasher>(map: &AHashIndexMap<K, V, S>) {
+        assert_eq!(map.len(), map.indices.len());
+        for (i, (k, _)) in map.iter().enumerate() {
+            assert_eq!(Some(i), map.get_index_of(k));
+        }
+    }
+
+    #[test]
+    fn test_insertion_order() {
+        let mut map: AHashIndexMap<usize, usize> = AHashIndexMap::new();
+        for i in (0..1000).rev() {
+            assert_eq!((999 - i, None), map.insert_full(i, i * 2));
+        }
+        assert_eq!((999, Some(0)), map.insert_full(0, 1));
+        assert_eq!((0..1000).rev().collect::<Vec<_>>(), keys(&map));
+        assert_eq!(Some((&999, &1998)), map.first());
+        assert_eq!(Some((&0, &1)), map.last());
+        assert_eq!(Some((&989, &1978)), map.get_index(10));
+        assert_eq!(None, map.get_index(1000));
+        assert_eq!(1978, map[10]);
+        assert_eq!(1978, map[&989]);
+        assert_eq!(Some((10, &989, &1978)), map.get_full(&989));
+        check_indices(&map);
+    }
+
+    #[test]
+    fn test_order_does_not_depend_on_hasher() {
+        let words = ["the", "quick", "brown", "fox", "jumps", "over", "lazy", "dog"];
+        let a: AHashIndexMap<_, _> = words.iter().map(|w| (*w, w.len())).collect();
+        for seed in 0..10 {
+            let mut b = AHashIndexMap::with_hasher(RandomState::with_seeds(seed, seed));
+            b.extend(words.iter().map(|w| (*w, w.len())));
+            assert_eq!(keys(&a), keys(&b));
+            assert_eq!(words.to_vec(), keys(&b));
+        }
+    }
+
+    #[test]
+    fn test_swap_remove() {
+        let mut map: AHashIndexMap<i32, i32> = (0..10).map(|i| (i, -i)).collect();
+        assert_eq!(Some(-2), map.swap_remove(&2));
+        assert_eq!(vec![0, 1, 9, 3, 4, 5, 6, 7, 8], keys(&map));
+        assert_eq!(Some((0, 0, 0)), map.swap_remove_full(&0));
+        assert_eq!(vec![8, 1, 9, 3, 4, 5, 6, 7], keys(&map));
+        assert_eq!(Some((9, -9)), map.swap_remove_index(2));
+        assert_eq!(vec![8, 1, 7, 3, 4, 5, 6], keys(&map));
+        assert_eq!(Some((6, -6)), map.swap_remove_index(6));
+        assert_eq!(Some((5, -5)), map.pop());
+        assert_eq!(None, map.swap_remove(&5));
+        assert_eq!(None, map.swap_remove_index(5));
+        assert_eq!(vec![8, 1, 7, 3, 4], keys(&map));
+        check_indices(&map);
+    }
+
+    #[test]
+    fn test_shift_remove() {
+        let mut map: AHashIndexMap<i32, i32> = (0..10).map(|i| (i, -i)).collect();
+        assert_eq!(Some(-2), map.shift_remove(&2));
+        assert_eq!(vec![0, 1, 3, 4, 5, 6, 7, 8, 9], keys(&map));
+        assert_eq!(Some((0, 0, 0)), map.shift_remove_full(&0));
+        assert_eq!(Some((5, -5)), map.shift_remove_index(3));
+        assert_eq!(Some((8, -8)), map.shift_remove_index(5));
+        assert_eq!(vec![1, 3, 4, 6, 7, 9], keys(&map));
+        assert_eq!(None, map.shift_remove(&0));
+        assert_eq!(None, map.shift_remove_index(6));
+        check_indices(&map);
+    }
+
+    #[test]
+    fn test_sort_and_reverse() {
+        let mut map: AHashIndexMap<&str, usize> = AHashIndexMap::new();
+        for (i, word) in ["pear", "apple", "fig", "banana"].iter().enumerate() {
+            map.insert(word, i);
+        }
+        map.sort_keys();
+        assert_eq!(vec!["apple", "banana", "fig", "pear"], keys(&map));
+        check_indices(&map);
+        map.sort_by(|k1, _, k2, _| k1.len().cmp(&k2.len()));
+        assert_eq!(vec!["fig", "pear", "apple", "banana"], keys(&map));
+        check_indices(&map);
+        map.reverse();
+        assert_eq!(vec!["banana", "apple", "pear", "fig"], keys(&map));
+        assert_eq!(vec![3, 1, 0, 2], map.values().copied().collect::<Vec<_>>());
+        check_indices(&map);
+    }
+
+    #[test]
+    fn test_retain_and_iterators() {
+        let mut map: AHashIndexMap<u32, u32> = (0..100).rev().map(|i| (i, i)).collect();
+        map.retain(|k, v| {
+            *v *= 2;
+            k % 3 == 0
+        });
+        assert_eq!((0..100).rev().filter(|k| k % 3 == 0).collect::<Vec<_>>(), keys(&map));
+        check_indices(&map);
+        for (k, v) in map.iter_mut() {
+            assert_eq!(*k * 2, *v);
+            *v = *k;
+        }
+        for v in map.values_mut() {
+            *v += 1;
+        }
+        if let Some((_, v)) = map.get_index_mut(0) {
+            *v = 0;
+        }
+        assert_eq!(Some((&0, &1)), map.iter().next_back());
+        let pairs: Vec<_> = map.clone().into_iter().collect();
+        assert_eq!((99, 0), pairs[0]);
+        assert_eq!((96, 97), pairs[1]);
+        assert_eq!(pairs.len(), map.iter().len());
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(None, map.get(&3));
+    }
+
+    #[test]
+    fn test_equality_ignores_order() {
+        let a = AHashIndexMap::from([(1, 'a'), (2, 'b')]);
+        let b = AHashIndexMap::from([(2, 'b'), (1, 'a')]);
+        assert_eq!(a, b);
+        assert_ne!(a, AHashIndexMap::from([(1, 'a')]));
+        assert_eq!("{1: 'a', 2: 'b'}", format!("{:?}", a));
+    }
+
+    #[test]
+    fn test_panicking_closures_leave_map_usable() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut map: AHashIndexMap<u32, u32> = (0..100).map(|i| (i, i)).collect();
+        let mut visited = 0;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            map.retain(|k, _| {
+                visited += 1;
+                if visited == 50 {
+                    panic!("predicate panicked");
+                }
+                k % 2 == 0
+            })
+        }));
+        assert!(result.is_err());
+        assert!(map.len() < 100);
+        check_indices(&map);
+        for k in map.keys().copied().collect::<Vec<_>>() {
+            assert_eq!(Some(&k), map.get(&k));
+        }
+        assert_eq!(None, map.get(&1));
+
+        let mut compared = 0;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            map.sort_by(|k1, _, k2, _| {
+                compared += 1;
+                if compared == 20 {
+                    panic!("comparison panicked");
+                }
+                k2.cmp(k1)
+            })
+        }));
+        assert!(result.is_err());
+        check_indices(&map);
+        assert_eq!(Some(&98), map.get(&98));
+        map.insert(1000, 1000);
+        assert_eq!(Some(&1000), map.get(&1000));
+        check_indices(&map);
+    }
+}
@@ -0,0 +1,491 @@
+//! `AHashIndexSet`, a hash set which iterates in insertion order, and the types its methods return.
+
+use crate::index_map::{self, AHashIndexMap};
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt::{self, Debug};
+use core::hash::{BuildHasher, Hash};
+use core::iter::{FromIterator, FusedIterator};
+use core::ops::Index;
+
+/// A hash set which keeps its values in the order they were inserted, using [`RandomState`](crate::RandomState) to
+/// hash them.
+///
+/// This is a thin wrapper around [`AHashIndexMap`] with `()` values, so it has the same properties: constant time
+/// lookups, access by position, and an iteration order which does not depend on the hasher.
+///
+/// Requires either the `std` or `alloc` feature to be enabled.
+///
+/// # Example
+///
+/// ```
+/// use ahash::AHashIndexSet;
+///
+/// let mut set: AHashIndexSet<_> = AHashIndexSet::new();
+/// set.insert("c");
+/// set.insert("a");
+/// set.insert("b");
+/// set.insert("c");
+/// assert_eq!(vec!["c", "a", "b"], set.iter().copied().collect::<Vec<_>>());
+///
+/// set.swap_remove("c");
+/// assert_eq!(Some(&"b"), set.get_index(0));
+/// ```
+#[derive(Clone)]
+pub struct AHashIndexSet<T, S = crate::RandomState>(AHashIndexMap<T, (), S>);
+
+impl<T, S> AHashIndexSet<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    pub fn new() -> Self {
+        AHashIndexSet(AHashIndexMap::new())
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        AHashIndexSet(AHashIndexMap::with_capacity(capacity))
+    }
+}
+
+impl<T, S> AHashIndexSet<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    pub fn with_hasher(hash_builder: S) -> Self {
+        AHashIndexSet(AHashIndexMap::with_hasher(hash_builder))
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        AHashIndexSet(AHashIndexMap::with_capacity_and_hasher(capacity, hash_builder))
+    }
+
+    /// Reserves capacity for at least `additional` more elements.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional)
+    }
+
+    /// Shrinks the capacity of the set as much as possible.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit()
+    }
+
+    /// Adds a value to the end of the set. Returns whether the value was newly inserted.
+    ///
+    /// If the value is already present its position is not changed.
+    #[inline]
+    pub fn insert(&mut self, value: T) -> bool {
+        self.0.insert(value, ()).is_none()
+    }
+
+    /// Adds a value to the set, and returns its position and whether it was newly inserted.
+    #[inline]
+    pub fn insert_full(&mut self, value: T) -> (usize, bool) {
+        let (index, old) = self.0.insert_full(value, ());
+        (index, old.is_none())
+    }
+
+    /// Returns `true` if the set contains the value.
+    #[inline]
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.contains_key(value)
+    }
+
+    /// Returns a reference to the value in the set, if any, that is equal to the given value.
+    #[inline]
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.get_key_value(value).map(|(k, _)| k)
+    }
+
+    /// Returns the position of the value in the set along with the value.
+    #[inline]
+    pub fn get_full<Q>(&self, value: &Q) -> Option<(usize, &T)>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.get_full(value).map(|(i, k, _)| (i, k))
+    }
+
+    /// Returns the position of the value in the set.
+    #[inline]
+    pub fn get_index_of<Q>(&self, value: &Q) -> Option<usize>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.get_index_of(value)
+    }
+
+    /// Removes a value from the set by replacing it with the last value. Returns whether the value was present.
+    ///
+    /// This takes constant time, but changes the position of the last value.
+    #[inline]
+    pub fn swap_remove<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.swap_remove(value).is_some()
+    }
+
+    /// Removes a value from the set by shifting all of the values that follow it. Returns whether the value was
+    /// present.
+    ///
+    /// This preserves the order of the remaining values, but takes linear time.
+    #[inline]
+    pub fn shift_remove<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.shift_remove(value).is_some()
+    }
+}
+
+impl<T, S> AHashIndexSet<T, S> {
+    /// Returns the number of elements the set can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Returns the number of elements in the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the set contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Removes all the values, keeping the allocated memory for reuse.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+
+    /// Returns a reference to the set's [`BuildHasher`].
+    #[inline]
+    pub fn hasher(&self) -> &S {
+        self.0.hasher()
+    }
+
+    /// Returns the value at the given position.
+    #[inline]
+    pub fn get_index(&self, index: usize) -> Option<&T> {
+        self.0.get_index(index).map(|(k, _)| k)
+    }
+
+    /// Returns the first value.
+    #[inline]
+    pub fn first(&self) -> Option<&T> {
+        self.0.first().map(|(k, _)| k)
+    }
+
+    /// Returns the last value.
+    #[inline]
+    pub fn last(&self) -> Option<&T> {
+        self.0.last().map(|(k, _)| k)
+    }
+
+    /// Removes the value at the given position by replacing it with the last value, and returns it.
+    #[inline]
+    pub fn swap_remove_index(&mut self, index: usize) -> Option<T> {
+        self.0.swap_remove_index(index).map(|(k, _)| k)
+    }
+
+    /// Removes the value at the given position by shifting all of the values that follow it, and returns it.
+    #[inline]
+    pub fn shift_remove_index(&mut self, index: usize) -> Option<T> {
+        self.0.shift_remove_index(index).map(|(k, _)| k)
+    }
+
+    /// Removes the last value and returns it.
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        self.0.pop().map(|(k, _)| k)
+    }
+
+    /// Retains only the values specified by the predicate, keeping their order.
+    #[inline]
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.0.retain(|k, _| f(k))
+    }
+
+    /// Sorts the values.
+    #[inline]
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.0.sort_keys()
+    }
+
+    /// Sorts the values using the provided comparison function. The sort is stable.
+    #[inline]
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.0.sort_by(|a, _, b, _| compare(a, b))
+    }
+
+    /// Reverses the order of the values.
+    #[inline]
+    pub fn reverse(&mut self) {
+        self.0.reverse()
+    }
+
+    /// An iterator visiting all values in order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter(self.0.keys())
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for AHashIndexSet<T>
+where
+    T: Eq + Hash,
+{
+    fn from(arr: [T; N]) -> Self {
+        AHashIndexSet::from_iter(arr)
+    }
+}
+
+/// Sets are equal if they contain the same values, regardless of their order.
+impl<T, S> PartialEq for AHashIndexSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    fn eq(&self, other: &AHashIndexSet<T, S>) -> bool {
+        self.0.eq(&other.0)
+    }
+}
+
+impl<T, S> Eq for AHashIndexSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+}
+
+impl<T, S> Index<usize> for AHashIndexSet<T, S> {
+    type Output = T;
+
+    /// Returns a reference to the value at the supplied position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is out of bounds.
+    #[inline]
+    fn index(&self, index: usize) -> &T {
+        self.get_index(index).expect("index out of bounds")
+    }
+}
+
+impl<T, S> Debug for AHashIndexSet<T, S>
+where
+    T: Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T, S> FromIterator<T> for AHashIndexSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        AHashIndexSet(AHashIndexMap::from_iter(iter.into_iter().map(|v| (v, ()))))
+    }
+}
+
+impl<'a, T, S> IntoIterator for &'a AHashIndexSet<T, S> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, S> IntoIterator for AHashIndexSet<T, S> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.0.into_iter())
+    }
+}
+
+impl<T, S> Extend<T> for AHashIndexSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.0.extend(iter.into_iter().map(|v| (v, ())))
+    }
+}
+
+impl<'a, T, S> Extend<&'a T> for AHashIndexSet<T, S>
+where
+    T: 'a + Eq + Hash + Copy,
+    S: BuildHasher,
+{
+    #[inline]
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied())
+    }
+}
+
+impl<T, S> Default for AHashIndexSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    #[inline]
+    fn default() -> AHashIndexSet<T, S> {
+        AHashIndexSet(AHashIndexMap::default())
+    }
+}
+
+/// An iterator over the values of an [`AHashIndexSet`] in order. Created by [`AHashIndexSet::iter`].
+pub struct Iter<'a, T>(index_map::Keys<'a, T, ()>);
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        self.0.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Iter(self.0.clone())
+    }
+}
+
+/// An owning iterator over the values of an [`AHashIndexSet`] in order. Created by its `into_iter` method.
+pub struct IntoIter<T>(index_map::IntoIter<T, ()>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        self.0.next().map(|(k, _)| k)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        self.0.next_back().map(|(k, _)| k)
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::RandomState;
+
+    fn values<T: Copy, S>(set: &AHashIndexSet<T, S>) -> Vec<T> {
+        set.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_insertion_order() {
+        let mut set: AHashIndexSet<i32> = AHashIndexSet::new();
+        assert_eq!((0, true), set.insert_full(30));
+        assert_eq!((1, true), set.insert_full(10));
+        assert!(set.insert(20));
+        assert_eq!((0, false), set.insert_full(30));
+        assert_eq!(vec![30, 10, 20], values(&set));
+        assert_eq!(Some((1, &10)), set.get_full(&10));
+        assert_eq!(Some(&30), set.first());
+        assert_eq!(Some(&20), set.last());
+        assert_eq!(10, set[1]);
+        assert_eq!(vec![20, 10, 30], set.clone().into_iter().rev().collect::<Vec<_>>());
+        assert_eq!("{30, 10, 20}", format!("{:?}", set));
+    }
+
+    #[test]
+    fn test_order_does_not_depend_on_hasher() {
+        let mut a = AHashIndexSet::with_hasher(RandomState::with_seeds(1, 2));
+        let mut b = AHashIndexSet::with_hasher(RandomState::with_seeds(3, 4));
+        a.extend((0..100).map(|i| i * 7 % 101));
+        b.extend((0..100).map(|i| i * 7 % 101));
+        assert_eq!(values(&a), values(&b));
+    }
+
+    #[test]
+    fn test_remove_and_sort() {
+        let mut set: AHashIndexSet<u32> = (0..8).rev().collect();
+        assert!(set.swap_remove(&7));
+        assert_eq!(vec![0, 6, 5, 4, 3, 2, 1], values(&set));
+        assert!(set.shift_remove(&6));
+        assert!(!set.shift_remove(&6));
+        assert_eq!(vec![0, 5, 4, 3, 2, 1], values(&set));
+        assert_eq!(Some(5), set.shift_remove_index(1));
+        assert_eq!(Some(0), set.swap_remove_index(0));
+        assert_eq!(vec![1, 4, 3, 2], values(&set));
+        assert_eq!(Some(2), set.pop());
+        set.retain(|v| *v != 4);
+        set.sort_by(|a, b| b.cmp(a));
+        assert_eq!(vec![3, 1], values(&set));
+        set.sort();
+        assert_eq!(vec![1, 3], values(&set));
+        assert_eq!(Some(1), set.get_index_of(&3));
+        assert_eq!(AHashIndexSet::from([3, 1]), set);
+    }
+}
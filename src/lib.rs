@@ -10,44 +10,131 @@
 //!
 //! aHash uses the hardware AES instruction on x86 processors to provide a keyed hash function.
 //! aHash is not a cryptographically secure hash.
+//!
+//! By default the AES instruction is only used if the `aes` target feature is enabled at compile time
+//! (for example with `-C target-feature=+aes`). If the `runtime-dispatch` feature is enabled, the CPU is instead
+//! checked for `aes` and `ssse3` support the first time a hasher is created, and the fastest available
+//! implementation is used from then on.
+//!
+//! Without AES-NI the fallback algorithm is used, unless the `soft-aes` feature is enabled. This computes the AES
+//! algorithm using a table based software implementation of the AES round, so hashes are the same as on a machine
+//! with AES-NI and SSSE3, at a much lower speed. (It has no effect if `runtime-dispatch` is used on x86)
 #![deny(clippy::correctness, clippy::complexity, clippy::perf)]
 #![allow(clippy::pedantic, clippy::cast_lossless, clippy::unreadable_literal)]
 #![cfg_attr(all(not(test), not(feature = "std")), no_std)]
 #![cfg_attr(feature = "specialize", feature(specialization))]
 
+#[cfg(any(feature = "std", feature = "alloc"))]
+extern crate alloc;
+
 #[macro_use]
 mod convert;
 
-#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "aes", not(miri)))]
+#[cfg(any(
+    all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "aes", not(miri)),
+    all(feature = "runtime-dispatch", any(target_arch = "x86", target_arch = "x86_64"), not(miri)),
+    feature = "soft-aes",
+    test
+))]
 mod aes_hash;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+pub mod alloc_hash_map;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+pub mod alloc_hash_set;
+mod batch;
+#[cfg(feature = "std")]
+pub mod concurrent_map;
+#[cfg(all(
+    feature = "runtime-dispatch",
+    any(target_arch = "x86", target_arch = "x86_64"),
+    not(target_feature = "aes"),
+    not(miri)
+))]
+mod dispatch_hash;
 mod fallback_hash;
+mod float;
+#[cfg(test)]
+mod hash_dos_test;
 #[cfg(test)]
 mod hash_quality_test;
+#[cfg(test)]
+mod hash_statistics_test;
+mod hashed;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub mod index_map;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub mod index_set;
 
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod macros;
 mod operations;
 #[cfg(feature = "std")]
 mod hash_map;
 #[cfg(feature = "std")]
 mod hash_set;
 mod random_state;
+#[cfg(test)]
+mod related_seeds_test;
+#[cfg(feature = "std")]
+pub mod rekeying_map;
+mod soft_aes;
 mod specialize;
+pub mod stable;
+mod stream;
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(feature = "std", allow(dead_code))] // Some of the table is only used by the `alloc` `AHashMap`
+mod table;
 
 #[cfg(feature = "compile-time-rng")]
 use const_random::const_random;
 
-#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "aes", not(miri)))]
+#[cfg(any(
+    all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "aes", not(miri)),
+    all(
+        feature = "soft-aes",
+        not(all(feature = "runtime-dispatch", any(target_arch = "x86", target_arch = "x86_64"), not(miri)))
+    )
+))]
 pub use crate::aes_hash::AHasher;
 
-#[cfg(not(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "aes", not(miri))))]
+#[cfg(all(
+    feature = "runtime-dispatch",
+    any(target_arch = "x86", target_arch = "x86_64"),
+    not(target_feature = "aes"),
+    not(miri)
+))]
+pub use crate::dispatch_hash::AHasher;
+
+#[cfg(not(any(
+    all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "aes", not(miri)),
+    all(feature = "runtime-dispatch", any(target_arch = "x86", target_arch = "x86_64"), not(miri)),
+    feature = "soft-aes"
+)))]
 pub use crate::fallback_hash::AHasher;
+pub use crate::float::{HashableF32, HashableF64};
+pub use crate::hashed::{Hashed, IdentityHasher, PassThroughState};
 pub use crate::random_state::RandomState;
 
 pub use crate::specialize::CallHasher;
+pub use crate::stable::v1::{const_hash, const_hash_str};
+pub use crate::stream::AHashStream;
 
 #[cfg(feature = "std")]
 pub use crate::hash_map::AHashMap;
 #[cfg(feature = "std")]
 pub use crate::hash_set::AHashSet;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+pub use crate::alloc_hash_map::AHashMap;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+pub use crate::alloc_hash_set::AHashSet;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub use crate::index_map::AHashIndexMap;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub use crate::index_set::AHashIndexSet;
+#[cfg(feature = "std")]
+pub use crate::concurrent_map::ConcurrentAHashMap;
+#[cfg(feature = "std")]
+pub use crate::rekeying_map::RekeyingAHashMap;
 use core::hash::Hasher;
 
 /// Provides a default [Hasher] compile time generated constants for keys.
@@ -130,7 +217,46 @@ impl Default for AHasher {
     }
 }
 
+/// Allows data to be hashed via [std::io::Write], for example using [std::io::copy].
+///
+/// Each call to `write` is equivalent to calling [Hasher::write] with the same data, and always consumes all of it.
+/// Because the hasher takes the length of each call into account, the resulting hash depends on how the data was
+/// divided between calls and not only on the data itself. (`io::copy` passes on whatever each `read` returned.)
+/// If the result should only depend on the data use [RandomState::hash_reader] instead.
+///
+/// # Example
+///
+/// ```
+/// use ahash::AHasher;
+/// use std::hash::Hasher;
+/// use std::io::Write;
+///
+/// let mut hasher = AHasher::new_with_keys(1234, 5678);
+/// hasher.write_all(b"Some data").unwrap();
+///
+/// let mut expected = AHasher::new_with_keys(1234, 5678);
+/// Hasher::write(&mut expected, b"Some data");
+/// assert_eq!(expected.finish(), hasher.finish());
+/// ```
+///
+/// [Hasher::write]: core::hash::Hasher::write
+/// [RandomState::hash_reader]: crate::RandomState::hash_reader
+#[cfg(feature = "std")]
+impl std::io::Write for AHasher {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        Hasher::write(self, buf);
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 /// Used for specialization. (Sealed)
+#[allow(dead_code)] // Only invoked when specialization is enabled.
 pub(crate) trait HasherExt: Hasher {
     #[doc(hidden)]
     fn hash_u64(self, value: u64) -> u64;
@@ -200,4 +326,32 @@ mod test {
     fn test_ahasher_construction() {
         let _ = AHasher::new_with_keys(1234, 5678);
     }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_io_write_matches_write() {
+        use std::io::Write;
+        let data = b"0123456789abcdefghijklmnopqrstuvwxyz0123456789abcdefghijklmnopqrstuvwxyz0123456789";
+        for chunk_size in [1, 3, 8, 16, 33, 100].iter() {
+            let mut hasher = AHasher::new_with_keys(1234, 5678);
+            let mut expected = AHasher::new_with_keys(1234, 5678);
+            for chunk in data.chunks(*chunk_size) {
+                assert_eq!(chunk.len(), Write::write(&mut hasher, chunk).unwrap());
+                Hasher::write(&mut expected, chunk);
+            }
+            hasher.flush().unwrap();
+            assert_eq!(expected.finish(), hasher.finish());
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_io_copy() {
+        let data = vec![7_u8; 1000];
+        let mut hasher = AHasher::new_with_keys(1234, 5678);
+        assert_eq!(1000, std::io::copy(&mut &data[..], &mut hasher).unwrap());
+        let mut expected = AHasher::new_with_keys(1234, 5678);
+        expected.write(&data);
+        assert_eq!(expected.finish(), hasher.finish());
+    }
 }
@@ -0,0 +1,151 @@
+/// Creates an [`AHashMap`](crate::AHashMap) containing the given key-value pairs.
+///
+/// The map is allocated with enough capacity for all of the pairs up front. By default it uses a new
+/// [`RandomState`](crate::RandomState); a different hasher can be supplied with `@hasher` as the first argument.
+/// If the same key appears more than once, the last value is kept.
+///
+/// # Examples
+///
+/// ```
+/// use ahash::{ahashmap, AHashMap};
+///
+/// let map = ahashmap! {
+///     "a" => 1,
+///     "b" => 2,
+/// };
+/// assert_eq!(map["a"], 1);
+/// assert_eq!(map["b"], 2);
+/// assert!(map.capacity() >= 2);
+///
+/// let empty: AHashMap<u32, u32> = ahashmap! {};
+/// assert!(empty.is_empty());
+/// ```
+///
+/// Using fixed keys:
+///
+/// ```
+/// use ahash::{ahashmap, RandomState};
+///
+/// let map = ahashmap! { @hasher RandomState::with_seeds(1, 2), 1 => "one", 2 => "two" };
+/// assert_eq!(map.hasher().hash_one(1), RandomState::with_seeds(1, 2).hash_one(1));
+/// assert_eq!(map[&2], "two");
+/// ```
+#[macro_export]
+macro_rules! ahashmap {
+    (@hasher $hasher:expr $(, $key:expr => $value:expr)* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut map = $crate::AHashMap::with_capacity_and_hasher($crate::__ahash_count!($($key),*), $hasher);
+        $(
+            map.insert($key, $value);
+        )*
+        map
+    }};
+    ($($key:expr => $value:expr),* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut map = <$crate::AHashMap<_, _>>::with_capacity($crate::__ahash_count!($($key),*));
+        $(
+            map.insert($key, $value);
+        )*
+        map
+    }};
+}
+
+/// Creates an [`AHashSet`](crate::AHashSet) containing the given values.
+///
+/// The set is allocated with enough capacity for all of the values up front. By default it uses a new
+/// [`RandomState`](crate::RandomState); a different hasher can be supplied with `@hasher` as the first argument.
+///
+/// # Examples
+///
+/// ```
+/// use ahash::{ahashset, AHashSet};
+///
+/// let set = ahashset! { "a", "b", "a" };
+/// assert_eq!(set.len(), 2);
+/// assert!(set.contains("a"));
+///
+/// let empty: AHashSet<u32> = ahashset! {};
+/// assert!(empty.is_empty());
+/// ```
+///
+/// Using fixed keys:
+///
+/// ```
+/// use ahash::{ahashset, RandomState};
+///
+/// let set = ahashset! { @hasher RandomState::with_seeds(1, 2), 1, 2, 3 };
+/// assert_eq!(set.hasher().hash_one(1), RandomState::with_seeds(1, 2).hash_one(1));
+/// assert!(set.contains(&3));
+/// ```
+#[macro_export]
+macro_rules! ahashset {
+    (@hasher $hasher:expr $(, $value:expr)* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut set = $crate::AHashSet::with_capacity_and_hasher($crate::__ahash_count!($($value),*), $hasher);
+        $(
+            set.insert($value);
+        )*
+        set
+    }};
+    ($($value:expr),* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut set = <$crate::AHashSet<_>>::with_capacity($crate::__ahash_count!($($value),*));
+        $(
+            set.insert($value);
+        )*
+        set
+    }};
+}
+
+/// Counts the expressions passed to it as a constant. (Used by the macros above to pre-size the collection)
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ahash_count {
+    (@unit $item:expr) => {
+        ()
+    };
+    ($($item:expr),*) => {
+        <[()]>::len(&[$($crate::__ahash_count!(@unit $item)),*])
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{AHashMap, AHashSet, RandomState};
+
+    #[test]
+    fn test_count() {
+        assert_eq!(0, __ahash_count!());
+        assert_eq!(3, __ahash_count!(1, "two", vec![3]));
+    }
+
+    #[test]
+    fn test_map_keeps_last_value() {
+        let map = ahashmap! { 1 => "a", 2 => "b", 1 => "c" };
+        assert_eq!(2, map.len());
+        assert_eq!("c", map[&1]);
+        assert!(map.capacity() >= 3);
+    }
+
+    #[test]
+    fn test_map_evaluates_each_expression_once() {
+        let mut calls = 0;
+        let mut next = || {
+            calls += 1;
+            calls
+        };
+        let map: AHashMap<i32, i32> = ahashmap! { next() => next(), next() => next() };
+        assert_eq!(AHashMap::from([(1, 2), (3, 4)]), map);
+    }
+
+    #[test]
+    fn test_with_hasher() {
+        let state = RandomState::with_seeds(1, 2);
+        let map = ahashmap! { @hasher state.clone(), };
+        let _: &AHashMap<u8, u8> = &map;
+        let set = ahashset! { @hasher state.clone(), 1, 2, 3, };
+        assert_eq!(AHashSet::from([1, 2, 3]), set);
+        assert_eq!(state.hash_one(7), set.hasher().hash_one(7));
+        assert_eq!(state.hash_one(7), map.hasher().hash_one(7));
+    }
+}
@@ -2,7 +2,7 @@ use crate::convert::*;
 
 /// This is a constant with a lot of special properties found by automated search.
 /// See the unit tests below. (Below are alternative values)
-#[cfg(all(target_feature = "ssse3", not(miri)))]
+#[allow(dead_code)] // Hardware AES builds without SSSE3 byte swap instead.
 const SHUFFLE_MASK: u128 = 0x020a0700_0c01030e_050f0d08_06090b04_u128;
 //const SHUFFLE_MASK: u128 = 0x000d0702_0a040301_05080f0c_0e0b0609_u128;
 //const SHUFFLE_MASK: u128 = 0x040A0700_030E0106_0D050F08_020B0C09_u128;
@@ -12,9 +12,22 @@ pub(crate) const fn folded_multiply(s: u64, by: u64) -> u64 {
     ((result & 0xffff_ffff_ffff_ffff) as u64) ^ ((result >> 64) as u64)
 }
 
+/// When the `runtime-dispatch` feature is enabled this is only invoked by the AES hasher, which is only selected
+/// if `ssse3` is detected at runtime.
+///
+/// Hardware AES builds without `ssse3` (such as `-C target-feature=+aes`) use a byte swap instead. Where the software
+/// AES round is used, the shuffle is done in software too so that the hashes match those of `ssse3` builds.
 #[inline(always)]
 pub(crate) fn shuffle(a: u128) -> u128 {
-    #[cfg(all(target_feature = "ssse3", not(miri)))]
+    #[cfg(any(
+        all(target_feature = "ssse3", not(miri)),
+        all(
+            feature = "runtime-dispatch",
+            any(target_arch = "x86", target_arch = "x86_64"),
+            not(target_feature = "aes"),
+            not(miri)
+        )
+    ))]
         {
             use core::mem::transmute;
             #[cfg(target_arch = "x86")]
@@ -22,13 +35,30 @@ pub(crate) fn shuffle(a: u128) -> u128 {
             #[cfg(target_arch = "x86_64")]
             use core::arch::x86_64::*;
             unsafe {
-                transmute(_mm_shuffle_epi8(transmute(a), transmute(SHUFFLE_MASK)))
+                transmute::<__m128i, u128>(_mm_shuffle_epi8(
+                    transmute::<u128, __m128i>(a),
+                    transmute::<u128, __m128i>(SHUFFLE_MASK),
+                ))
             }
         }
-    #[cfg(not(all(target_feature = "ssse3", not(miri))))]
+    #[cfg(all(
+        not(all(target_feature = "ssse3", not(miri))),
+        all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "aes", not(miri))
+    ))]
         {
             a.swap_bytes()
         }
+    #[cfg(not(any(
+        all(target_feature = "ssse3", not(miri)),
+        all(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            any(target_feature = "aes", feature = "runtime-dispatch"),
+            not(miri)
+        )
+    )))]
+        {
+            crate::soft_aes::shuffle_bytes(a, SHUFFLE_MASK)
+        }
 }
 
 #[allow(unused)] //not used by fallback
@@ -54,7 +84,7 @@ pub(crate) fn add_by_64s(a: [u64; 2], b: [u64; 2]) -> [u64; 2] {
         use core::arch::x86::*;
         #[cfg(target_arch = "x86_64")]
         use core::arch::x86_64::*;
-        transmute(_mm_add_epi64(transmute(a), transmute(b)))
+        transmute::<__m128i, [u64; 2]>(_mm_add_epi64(transmute::<[u64; 2], __m128i>(a), transmute::<[u64; 2], __m128i>(b)))
     }
 }
 
@@ -64,7 +94,11 @@ pub(crate) fn add_by_64s(a: [u64; 2], b: [u64; 2]) -> [u64; 2] {
     [a[0].wrapping_add(b[0]), a[1].wrapping_add(b[1])]
 }
 
-#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "aes", not(miri)))]
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    any(target_feature = "aes", feature = "runtime-dispatch"),
+    not(miri)
+))]
 #[allow(unused)]
 #[inline(always)]
 pub(crate) fn aesenc(value: u128, xor: u128) -> u128 {
@@ -74,11 +108,15 @@ pub(crate) fn aesenc(value: u128, xor: u128) -> u128 {
     use core::arch::x86_64::*;
     use core::mem::transmute;
     unsafe {
-        let value = transmute(value);
-        transmute(_mm_aesenc_si128(value, transmute(xor)))
+        let value = transmute::<u128, __m128i>(value);
+        transmute::<__m128i, u128>(_mm_aesenc_si128(value, transmute::<u128, __m128i>(xor)))
     }
 }
-#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "aes", not(miri)))]
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    any(target_feature = "aes", feature = "runtime-dispatch"),
+    not(miri)
+))]
 #[allow(unused)]
 #[inline(always)]
 pub(crate) fn aesdec(value: u128, xor: u128) -> u128 {
@@ -88,11 +126,40 @@ pub(crate) fn aesdec(value: u128, xor: u128) -> u128 {
     use core::arch::x86_64::*;
     use core::mem::transmute;
     unsafe {
-        let value = transmute(value);
-        transmute(_mm_aesdec_si128(value, transmute(xor)))
+        let value = transmute::<u128, __m128i>(value);
+        transmute::<__m128i, u128>(_mm_aesdec_si128(value, transmute::<u128, __m128i>(xor)))
     }
 }
 
+#[cfg(not(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    any(target_feature = "aes", feature = "runtime-dispatch"),
+    not(miri)
+)))]
+#[allow(unused)] //not used by fallback
+pub(crate) use crate::soft_aes::{aesdec, aesenc};
+
+/// Returns true if the AES hasher can't be called directly on this CPU. With `runtime-dispatch` the functions above
+/// use AES-NI and SSSE3 without them being enabled at compile time, and only `dispatch_hash` checks that the CPU has
+/// them, so tests which use the AES hasher outside of it are skipped when it doesn't.
+#[cfg(test)]
+pub(crate) fn aes_unavailable() -> bool {
+    #[cfg(all(
+        feature = "runtime-dispatch",
+        any(target_arch = "x86", target_arch = "x86_64"),
+        not(target_feature = "aes"),
+        not(miri)
+    ))]
+    return !crate::dispatch_hash::aes_available();
+    #[cfg(not(all(
+        feature = "runtime-dispatch",
+        any(target_arch = "x86", target_arch = "x86_64"),
+        not(target_feature = "aes"),
+        not(miri)
+    )))]
+    return false;
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -163,14 +230,17 @@ mod test {
     //     count
     // }
 
-    #[cfg(all(
+    #[cfg(not(all(
         any(target_arch = "x86", target_arch = "x86_64"),
-        target_feature = "ssse3",
         target_feature = "aes",
+        not(target_feature = "ssse3"),
         not(miri)
-    ))]
+    )))]
     #[test]
     fn test_shuffle_does_not_collide_with_aes() {
+        if aes_unavailable() {
+            return;
+        }
         let mut value: [u8; 16] = [0; 16];
         let zero_mask_enc = aesenc(0, 0);
         let zero_mask_dec = aesdec(0, 0);
@@ -199,6 +269,9 @@ mod test {
 
     #[test]
     fn test_shuffle_contains_each_value() {
+        if aes_unavailable() {
+            return;
+        }
         let value: [u8; 16] = 0x00010203_04050607_08090A0B_0C0D0E0F_u128.convert();
         let shuffled: [u8; 16] = shuffle(value.convert()).convert();
         for index in 0..16_u8 {
@@ -208,6 +281,9 @@ mod test {
 
     #[test]
     fn test_shuffle_moves_every_value() {
+        if aes_unavailable() {
+            return;
+        }
         let mut value: [u8; 16] = [0; 16];
         for index in 0..16 {
             value[index] = 1;
@@ -219,6 +295,9 @@ mod test {
 
     #[test]
     fn test_shuffle_moves_high_bits() {
+        if aes_unavailable() {
+            return;
+        }
         assert!(
             shuffle(1) > (1_u128 << 80),
             "Low bits must be moved to other half {:?} -> {:?}",
@@ -259,13 +338,17 @@ mod test {
         );
     }
 
-    #[cfg(all(
+    #[cfg(not(all(
         any(target_arch = "x86", target_arch = "x86_64"),
-        target_feature = "ssse3",
+        target_feature = "aes",
+        not(target_feature = "ssse3"),
         not(miri)
-    ))]
+    )))]
     #[test]
     fn test_shuffle_does_not_loop() {
+        if aes_unavailable() {
+            return;
+        }
         let numbered = 0x00112233_44556677_8899AABB_CCDDEEFF;
         let mut shuffled = shuffle(numbered);
         for count in 0..100 {
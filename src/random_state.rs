@@ -1,7 +1,14 @@
 use crate::convert::Convert;
+use crate::specialize::CallHasher;
+use crate::AHashStream;
 use crate::AHasher;
 use core::fmt;
 use core::hash::BuildHasher;
+use core::hash::Hash;
+#[cfg(feature = "std")]
+use core::hash::Hasher;
+#[cfg(feature = "runtime-rng")]
+use core::sync::atomic::AtomicBool;
 use core::sync::atomic::AtomicUsize;
 use core::sync::atomic::Ordering;
 
@@ -26,6 +33,45 @@ static SEED: AtomicUsize = AtomicUsize::new(const_random!(u64) as usize);
 #[cfg(any(not(feature = "compile-time-rng"), test))]
 static SEED: AtomicUsize = AtomicUsize::new(INCREMENT as usize);
 
+// Random data obtained from the OS the first time a `RandomState` is created. If the OS cannot provide any these
+// remain zero and only the other sources of randomness are used.
+#[cfg(feature = "runtime-rng")]
+static OS_SEED: [AtomicUsize; 4] = [
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+];
+
+#[cfg(feature = "runtime-rng")]
+static OS_SEED_LOADED: AtomicBool = AtomicBool::new(false);
+
+/// Returns random keys obtained from the OS. These are only read once per process.
+/// (If multiple threads race to initialize them, each value stored is still random so this is harmless.)
+#[cfg(feature = "runtime-rng")]
+#[inline]
+fn os_seed() -> [u64; 2] {
+    if !OS_SEED_LOADED.load(Ordering::Acquire) {
+        let mut buffer = [0_u8; 32];
+        if getrandom::getrandom(&mut buffer).is_ok() {
+            let values: [u64; 4] = buffer.convert();
+            for (seed, value) in OS_SEED.iter().zip(values.iter()) {
+                seed.store(*value as usize, Ordering::Relaxed);
+            }
+            // Also make the sequence of values taken by the counter unpredictable.
+            SEED.fetch_xor(values[0].rotate_left(32) as usize, Ordering::Relaxed);
+        }
+        OS_SEED_LOADED.store(true, Ordering::Release);
+    }
+    // Combined this way so that 128 bits are retained even when usize is only 32 bits.
+    let load = |i: usize| OS_SEED[i].load(Ordering::Relaxed) as u64;
+    [load(0) ^ load(2).rotate_left(32), load(1) ^ load(3).rotate_left(32)]
+}
+
+/// The size of the blocks passed to `write` by `RandomState::hash_reader`.
+#[cfg(feature = "std")]
+const READER_BLOCK_SIZE: usize = 4096;
+
 /// Provides a [Hasher] factory. This is typically used (e.g. by [HashMap]) to create
 /// [AHasher]s in order to hash the keys of the map. See `build_hasher` below.
 ///
@@ -48,8 +94,17 @@ impl fmt::Debug for RandomState {
 }
 
 impl RandomState {
+    /// Creates a new `RandomState` with keys that differ from every other `RandomState` in the process.
+    ///
+    /// The keys are derived from compile time generated constants (if the `compile-time-rng` feature is enabled),
+    /// a global counter, and memory addresses. If the `runtime-rng` feature is enabled random data obtained from the
+    /// operating system is mixed in as well, so that the keys do not depend on ASLR or the binary remaining secret.
     #[inline]
     pub fn new() -> RandomState {
+        #[cfg(feature = "runtime-rng")]
+        let [os0, os1] = os_seed();
+        #[cfg(not(feature = "runtime-rng"))]
+        let [os0, os1] = [0_u64, 0_u64];
         //Using a self pointer. When running with ASLR this is a random value.
         let previous = SEED.load(Ordering::Relaxed) as u64;
         let stack_mem_loc = &previous as *const _ as u64;
@@ -60,7 +115,7 @@ impl RandomState {
             .wrapping_mul(MULTIPLE)
             .rotate_right(31);
         SEED.store(current_seed as usize, Ordering::Relaxed);
-        let (k0, k1, k2, k3) = scramble_keys(&SEED as *const _ as u64, current_seed);
+        let (k0, k1, k2, k3) = scramble_keys((&SEED as *const _ as u64) ^ os0, current_seed ^ os1);
         RandomState { k0, k1, k2, k3 }
     }
 
@@ -69,6 +124,219 @@ impl RandomState {
         let (k0, k1, k2, k3) = scramble_keys(k0, k1);
         RandomState { k0, k1, k2, k3 }
     }
+
+    /// Allows for explicitly setting all four of the keys used.
+    ///
+    /// Unlike `with_seeds` the keys are used as is, without being scrambled, so all 256 bits of the provided keys
+    /// affect the output. Because of this the keys must be random. (Low entropy values such as small integers
+    /// should be passed to `with_seeds` instead.)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ahash::RandomState;
+    ///
+    /// let state = RandomState::new();
+    /// let [k0, k1, k2, k3] = state.keys();
+    /// // Store the keys somewhere...
+    /// let copy = RandomState::with_keys(k0, k1, k2, k3);
+    /// ```
+    pub const fn with_keys(k0: u64, k1: u64, k2: u64, k3: u64) -> RandomState {
+        RandomState { k0, k1, k2, k3 }
+    }
+
+    /// Returns the keys used by this `RandomState`.
+    ///
+    /// Passing these to `with_keys` will create a `RandomState` which produces identical hashes. As anyone who knows
+    /// them can compute the hashes, they should be treated as a secret.
+    pub const fn keys(&self) -> [u64; 4] {
+        [self.k0, self.k1, self.k2, self.k3]
+    }
+
+    /// Calculates the hash of a single value.
+    ///
+    /// This is the same as `BuildHasher::hash_one`: the value is hashed via its `Hash` implementation with a new
+    /// hasher from `build_hasher`. References are hashed the same way as the value they refer to.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ahash::RandomState;
+    ///
+    /// let state = RandomState::new();
+    /// assert_eq!(state.hash_one("Some key"), state.hash_one("Some key"));
+    /// assert_ne!(state.hash_one("Some key"), state.hash_one("Other key"));
+    /// ```
+    #[inline]
+    pub fn hash_one<T: Hash>(&self, x: T) -> u64 {
+        BuildHasher::hash_one(self, x)
+    }
+
+    /// Calculates the hash of a single value using the fastest available method.
+    ///
+    /// This is equivalent to `x.get_hash(self.build_hasher())` (see [CallHasher]), so when the `specialize` feature is
+    /// enabled the faster paths for primitives, strings and byte slices are used automatically. In that case the
+    /// result is not the same as that of `hash_one` for those types, so the two must not be mixed for the same keys.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ahash::RandomState;
+    ///
+    /// let state = RandomState::new();
+    /// assert_eq!(state.hash_one_specialized(17), state.hash_one_specialized(17));
+    /// ```
+    ///
+    /// [CallHasher]: crate::CallHasher
+    #[inline]
+    pub fn hash_one_specialized<T: Hash>(&self, x: T) -> u64 {
+        x.get_hash(self.build_hasher())
+    }
+
+    /// Calculates the full 128 bit hash of a single value. (See `AHasher::finish_u128`.)
+    ///
+    /// The lower 64 bits are the result of `hash_one`.
+    #[inline]
+    pub fn hash_one_u128<T: Hash>(&self, x: T) -> u128 {
+        let mut hasher = self.build_hasher();
+        x.hash(&mut hasher);
+        hasher.finish_u128()
+    }
+
+    /// Hashes each of the `keys`, storing the results in `out`.
+    ///
+    /// Each result is the same as would be obtained by calling `write_u64` with the key on a new hasher from
+    /// `build_hasher` followed by `finish`, which is also what `hash_one` does for a `u64`. Because several keys are
+    /// processed in parallel this is faster than hashing the keys one at a time.
+    ///
+    /// # Panics
+    ///
+    /// If `keys` and `out` are not the same length.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ahash::RandomState;
+    /// use std::hash::{BuildHasher, Hasher};
+    ///
+    /// let state = RandomState::new();
+    /// let keys = [1, 2, 3, 4, 5];
+    /// let mut hashes = [0; 5];
+    /// state.hash_u64_slice(&keys, &mut hashes);
+    ///
+    /// let mut hasher = state.build_hasher();
+    /// hasher.write_u64(3);
+    /// assert_eq!(hasher.finish(), hashes[2]);
+    /// ```
+    #[inline]
+    pub fn hash_u64_slice(&self, keys: &[u64], out: &mut [u64]) {
+        assert_eq!(keys.len(), out.len(), "keys and out must be the same length");
+        self.build_hasher().hash_u64_batch(keys, out)
+    }
+
+    /// Hashes each of the `keys`, storing the results in `out`.
+    ///
+    /// Each result is the same as would be obtained by calling `write` with the key on a new hasher from
+    /// `build_hasher` followed by `finish`. (Note this is not the same as the `Hash` implementation of `[u8]`, which
+    /// also hashes the length.)
+    ///
+    /// Unlike `hash_u64_slice` this is provided for convenience only. Keys of different lengths take different paths
+    /// through the hasher, so there is no speed advantage over hashing them one at a time.
+    ///
+    /// # Panics
+    ///
+    /// If `keys` and `out` are not the same length.
+    #[inline]
+    pub fn hash_bytes_slice(&self, keys: &[&[u8]], out: &mut [u64]) {
+        assert_eq!(keys.len(), out.len(), "keys and out must be the same length");
+        self.build_hasher().hash_slice_batch(keys, out)
+    }
+
+    /// Constructs a new [AHashStream] with the same keys as the hashers returned by `build_hasher`.
+    ///
+    /// [AHashStream]: crate::AHashStream
+    #[inline]
+    pub fn build_stream(&self) -> AHashStream {
+        AHashStream::from_hasher(self.build_hasher())
+    }
+
+    /// Hashes all of the data that can be read from `reader`.
+    ///
+    /// The data is passed to `write` in consecutive blocks of 4096 bytes (the last of which may be shorter), followed
+    /// by `finish`. No block is written if there is no data. This means the result depends only on the data, and not
+    /// on how many bytes each call to `read` happens to return.
+    ///
+    /// Any error returned by the reader (other than `Interrupted`, which is retried) is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ahash::RandomState;
+    ///
+    /// let state = RandomState::new();
+    /// let data: &[u8] = b"Some data";
+    /// let hash = state.hash_reader(data).unwrap();
+    /// assert_eq!(hash, state.hash_reader(data).unwrap());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn hash_reader<R: std::io::Read>(&self, mut reader: R) -> std::io::Result<u64> {
+        let mut hasher = self.build_hasher();
+        let mut buffer = [0_u8; READER_BLOCK_SIZE];
+        loop {
+            let mut filled = 0;
+            while filled < buffer.len() {
+                match reader.read(&mut buffer[filled..]) {
+                    Ok(0) => break,
+                    Ok(read) => filled += read,
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                }
+            }
+            if filled > 0 {
+                hasher.write(&buffer[..filled]);
+            }
+            if filled < buffer.len() {
+                return Ok(hasher.finish());
+            }
+        }
+    }
+
+    /// Exports the keys of this `RandomState` so that it can be recreated using `from_bytes`, possibly in another
+    /// process or on another machine. The keys are stored in little endian order, so the format does not depend on
+    /// the platform.
+    ///
+    /// Anyone who knows these bytes can compute the hashes, so they should be treated as a secret.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ahash::RandomState;
+    /// use std::hash::{BuildHasher, Hash, Hasher};
+    ///
+    /// let state = RandomState::new();
+    /// let restored = RandomState::from_bytes(state.to_bytes());
+    ///
+    /// let mut hasher = state.build_hasher();
+    /// let mut restored_hasher = restored.build_hasher();
+    /// "Some key".hash(&mut hasher);
+    /// "Some key".hash(&mut restored_hasher);
+    /// assert_eq!(hasher.finish(), restored_hasher.finish());
+    /// ```
+    pub fn to_bytes(&self) -> [u8; 32] {
+        let keys: [u64; 4] = [self.k0.to_le(), self.k1.to_le(), self.k2.to_le(), self.k3.to_le()];
+        keys.convert()
+    }
+
+    /// Recreates a `RandomState` from the bytes returned by `to_bytes`.
+    pub fn from_bytes(bytes: [u8; 32]) -> RandomState {
+        let keys: [u64; 4] = bytes.convert();
+        RandomState::with_keys(
+            u64::from_le(keys[0]),
+            u64::from_le(keys[1]),
+            u64::from_le(keys[2]),
+            u64::from_le(keys[3]),
+        )
+    }
 }
 
 /// This is based on the fallback hasher
@@ -106,7 +374,8 @@ impl BuildHasher for RandomState {
     /// [AHasher]s that will return different hashcodes, but [Hasher]s created from the same [BuildHasher]
     /// will generate the same hashes for the same input data.
     ///
-    /// ** - only if the `compile-time-rng` feature is enabled.
+    /// ** - only if the `compile-time-rng` feature is enabled. If the `runtime-rng` feature is enabled, random data
+    /// obtained from the operating system is also used.
     ///
     /// # Examples
     ///
@@ -137,9 +406,27 @@ impl BuildHasher for RandomState {
     }
 }
 
+/// Serializes the keys (in the same order as returned by `keys()`). Anyone who can see the serialized value can
+/// compute the hashes, so it should be treated as a secret.
+#[cfg(feature = "serde")]
+impl serde::Serialize for RandomState {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.keys().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for RandomState {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<RandomState, D::Error> {
+        let [k0, k1, k2, k3] = <[u64; 4]>::deserialize(deserializer)?;
+        Ok(RandomState::with_keys(k0, k1, k2, k3))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use core::hash::Hasher;
 
     #[test]
     fn test_const_rand_disabled() {
@@ -150,4 +437,235 @@ mod test {
     fn test_with_seeds_const() {
         const _CONST_RANDOM_STATE: RandomState = RandomState::with_seeds(17, 19);
     }
+
+    #[test]
+    fn test_with_keys_round_trip() {
+        let state = RandomState::new();
+        let [k0, k1, k2, k3] = state.keys();
+        let copy = RandomState::with_keys(k0, k1, k2, k3);
+        let mut a = state.build_hasher();
+        let mut b = copy.build_hasher();
+        a.write(b"round trip");
+        b.write(b"round trip");
+        assert_eq!(a.finish(), b.finish());
+    }
+
+    #[test]
+    fn test_every_key_bit_matters() {
+        const KEYS: [u64; 4] = [
+            0x243F_6A88_85A3_08D3,
+            0x1319_8A2E_0370_7344,
+            0xA409_3822_299F_31D0,
+            0x082E_FA98_EC4E_6C89,
+        ];
+        // Inputs of these lengths use all of the keys in both the AES and fallback algorithms.
+        const INPUTS: [&[u8]; 3] = [b"01234", b"0123456789abcdef", b"0123456789abcdefghijklmnopqrstuv"];
+        fn hash(keys: [u64; 4], input: &[u8]) -> u64 {
+            let mut hasher = RandomState::with_keys(keys[0], keys[1], keys[2], keys[3]).build_hasher();
+            hasher.write(input);
+            hasher.finish()
+        }
+        for input in INPUTS.iter() {
+            let base = hash(KEYS, input);
+            for key in 0..4 {
+                for bit in 0..64 {
+                    let mut keys = KEYS;
+                    keys[key] ^= 1 << bit;
+                    assert_ne!(
+                        base,
+                        hash(keys, input),
+                        "Flipping bit {} of key {} did not change the hash of {:?}",
+                        bit,
+                        key,
+                        input
+                    );
+                }
+            }
+        }
+    }
+
+    fn assert_same_hashes(a: &RandomState, b: &RandomState) {
+        let inputs: [&[u8]; 4] = [b"", b"a", b"0123456789abcdef", b"0123456789abcdefghijklmnopqrstuvwxyz"];
+        for input in inputs.iter() {
+            let mut hasher_a = a.build_hasher();
+            let mut hasher_b = b.build_hasher();
+            hasher_a.write(input);
+            hasher_b.write(input);
+            assert_eq!(hasher_a.finish(), hasher_b.finish());
+            hasher_a.write_u64(42);
+            hasher_b.write_u64(42);
+            assert_eq!(hasher_a.finish(), hasher_b.finish());
+        }
+    }
+
+    #[test]
+    fn test_hash_one() {
+        let state = RandomState::with_seeds(1, 2);
+        assert_eq!(state.hash_one(17_u64), BuildHasher::hash_one(&state, 17_u64));
+        assert_eq!(state.hash_one("foo"), BuildHasher::hash_one(&state, "foo"));
+        assert_eq!(
+            state.hash_one((1_u32, "foo")),
+            BuildHasher::hash_one(&state, (1_u32, "foo"))
+        );
+        let owned = "foo".to_string();
+        assert_eq!(state.hash_one("foo"), state.hash_one(&owned));
+        assert_ne!(state.hash_one("foo"), state.hash_one("bar"));
+    }
+
+    #[test]
+    fn test_hash_one_specialized() {
+        let state = RandomState::with_seeds(1, 2);
+        assert_eq!(state.hash_one_specialized(17_u64), 17_u64.get_hash(state.build_hasher()));
+        assert_eq!(state.hash_one_specialized("foo"), "foo".get_hash(state.build_hasher()));
+        let owned = "foo".to_string();
+        assert_eq!(state.hash_one_specialized("foo"), state.hash_one_specialized(&owned));
+        assert_ne!(state.hash_one_specialized("foo"), state.hash_one_specialized("bar"));
+    }
+
+    #[test]
+    fn test_hash_one_u128() {
+        let state = RandomState::with_seeds(1, 2);
+        let mut hasher = state.build_hasher();
+        "foo".hash(&mut hasher);
+        assert_eq!(state.hash_one_u128("foo"), hasher.finish_u128());
+        assert_eq!(state.hash_one_u128("foo") as u64, state.hash_one("foo"));
+        assert_eq!(state.hash_one_u128(17_u64) as u64, state.hash_one(17_u64));
+        assert_ne!(state.hash_one_u128("foo"), state.hash_one_u128("bar"));
+    }
+
+    #[test]
+    fn test_hash_u64_slice_matches_individual() {
+        let state = RandomState::with_seeds(1, 2);
+        let keys: Vec<u64> = (0..13_u64).map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15)).collect();
+        // Every length up to this covers each possible number of keys left over after the full lanes.
+        for len in 0..=keys.len() {
+            let mut out = vec![0; len];
+            state.hash_u64_slice(&keys[..len], &mut out);
+            for (key, hash) in keys.iter().zip(out.iter()) {
+                let mut hasher = state.build_hasher();
+                hasher.write_u64(*key);
+                assert_eq!(hasher.finish(), *hash);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hash_bytes_slice_matches_individual() {
+        let state = RandomState::with_seeds(1, 2);
+        let data = b"0123456789abcdefghijklmnopqrstuvwxyz0123456789abcdefghijklmnopqrstuvwxyz0123456789";
+        let keys: Vec<&[u8]> = (0..13).map(|i| &data[i..i * 6]).collect();
+        for len in 0..=keys.len() {
+            let mut out = vec![0; len];
+            state.hash_bytes_slice(&keys[..len], &mut out);
+            for (key, hash) in keys.iter().zip(out.iter()) {
+                let mut hasher = state.build_hasher();
+                hasher.write(key);
+                assert_eq!(hasher.finish(), *hash);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_hash_u64_slice_length_mismatch() {
+        RandomState::with_seeds(1, 2).hash_u64_slice(&[1, 2, 3], &mut [0; 2]);
+    }
+
+    /// Returns at most `max` bytes per call, and is interrupted every other call.
+    #[cfg(feature = "std")]
+    struct ChoppyReader<'a> {
+        data: &'a [u8],
+        max: usize,
+        interrupt: bool,
+    }
+
+    #[cfg(feature = "std")]
+    impl std::io::Read for ChoppyReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.interrupt = !self.interrupt;
+            if self.interrupt {
+                return Err(std::io::ErrorKind::Interrupted.into());
+            }
+            let len = buf.len().min(self.max).min(self.data.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Ok(len)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_hash_reader_independent_of_reads() {
+        let state = RandomState::with_seeds(1, 2);
+        let data: Vec<u8> = (0..10_000_u32).map(|i| (i * 7 % 251) as u8).collect();
+        for len in [0, 1, 100, READER_BLOCK_SIZE, READER_BLOCK_SIZE + 1, 10_000].iter() {
+            let data = &data[..*len];
+            let mut expected = state.build_hasher();
+            for block in data.chunks(READER_BLOCK_SIZE) {
+                expected.write(block);
+            }
+            let expected = expected.finish();
+            assert_eq!(expected, state.hash_reader(data).unwrap());
+            for max in [1, 7, 1000, 5000].iter() {
+                let reader = ChoppyReader {
+                    data,
+                    max: *max,
+                    interrupt: false,
+                };
+                assert_eq!(expected, state.hash_reader(reader).unwrap());
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_hash_reader_error() {
+        struct Failing;
+        impl std::io::Read for Failing {
+            fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::BrokenPipe.into())
+            }
+        }
+        let error = RandomState::with_seeds(1, 2).hash_reader(Failing).unwrap_err();
+        assert_eq!(std::io::ErrorKind::BrokenPipe, error.kind());
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let state = RandomState::new();
+        let restored = RandomState::from_bytes(state.to_bytes());
+        assert_eq!(state.keys(), restored.keys());
+        assert_same_hashes(&state, &restored);
+    }
+
+    #[test]
+    fn test_bytes_are_little_endian() {
+        let state = RandomState::with_keys(0x0807_0605_0403_0201, 0, 0, 0x2020_2020_2020_2020);
+        let bytes = state.to_bytes();
+        assert_eq!([1, 2, 3, 4, 5, 6, 7, 8], bytes[..8]);
+        assert_eq!([0x20; 8], bytes[24..]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let state = RandomState::new();
+        let json = serde_json::to_string(&state).unwrap();
+        let restored: RandomState = serde_json::from_str(&json).unwrap();
+        assert_eq!(state.keys(), restored.keys());
+        assert_same_hashes(&state, &restored);
+    }
+
+    #[cfg(feature = "runtime-rng")]
+    #[test]
+    fn test_os_seed() {
+        let seed = os_seed();
+        assert_ne!([0, 0], seed);
+        assert!(OS_SEED_LOADED.load(Ordering::Relaxed));
+        assert_eq!(seed, os_seed());
+
+        let a = RandomState::new();
+        let b = RandomState::new();
+        assert_ne!((a.k0, a.k1, a.k2, a.k3), (b.k0, b.k1, b.k2, b.k3));
+    }
 }
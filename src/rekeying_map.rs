@@ -0,0 +1,602 @@
+use crate::AHashMap;
+use std::borrow::Borrow;
+use std::collections::hash_map::{self, IterMut, ValuesMut};
+use std::fmt::{self, Debug};
+use std::hash::{BuildHasher, Hash};
+use std::iter::FromIterator;
+use std::mem;
+use std::ops::Deref;
+
+/// If more keys than this have hashes that select the same bucket, the map is assumed to be under attack.
+///
+/// With a good hash function and at most one key per bucket on average, the fullest bucket is expected to hold fewer
+/// than 10 keys even for maps with millions of entries, so this is practically never reached by accident.
+const MAX_BUCKET_LOAD: u32 = 32;
+
+/// The minimum number of buckets tracked.
+const MIN_BUCKETS: usize = 16;
+
+/// A [`AHashMap`] which detects hash flooding and recovers from it by rehashing all of its entries with new keys.
+///
+/// A `HashMap` degrades to linear time operations if an attacker can find many keys whose hashes select the same
+/// bucket. aHash is designed to make this impossible without knowing the keys, but this map provides defense in depth
+/// for places where the keys are under the control of an untrusted party (such as when parsing requests).
+///
+/// To do so it keeps count of how many of its keys have hashes that select each bucket. If any bucket becomes much
+/// fuller than is plausible with unpredictable hashes, a new hasher is obtained via `S::default()` and all of the
+/// entries are moved to a new map using it. For [`RandomState`](crate::RandomState) this selects new random keys.
+/// If rekeying does not help (for example because many keys have the same `Hash` value), another rekey is only
+/// attempted after the map has doubled in size, so this cannot cause quadratic behavior.
+///
+/// This costs an additional hash computation per insert or removal and 4 bytes of memory per entry. Only methods
+/// which do not modify the map are available via `Deref`.
+///
+/// # Example
+///
+/// ```
+/// use ahash::RekeyingAHashMap;
+///
+/// let mut map: RekeyingAHashMap<String, u32> = RekeyingAHashMap::new();
+/// map.insert("user".to_string(), 1);
+/// assert_eq!(Some(&1), map.get("user"));
+/// assert_eq!(0, map.rekey_count());
+/// ```
+pub struct RekeyingAHashMap<K, V, S = crate::RandomState> {
+    map: AHashMap<K, V, S>,
+    /// The number of keys whose hash selects each bucket. (Always a power of two long)
+    bucket_loads: Vec<u32>,
+    rekeys: usize,
+    /// The size the map must reach before another rekey is attempted.
+    next_rekey_len: usize,
+}
+
+impl<K, V, S> RekeyingAHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    /// Creates an empty map, with a hasher obtained from `S::default()`.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Creates an empty map with space for at least `capacity` entries, with a hasher obtained from `S::default()`.
+    ///
+    /// The bucket counts are also sized for `capacity` entries, so they will not need to be recomputed until the map
+    /// grows beyond it.
+    pub fn with_capacity(capacity: usize) -> Self {
+        RekeyingAHashMap {
+            map: AHashMap::with_capacity(capacity),
+            bucket_loads: vec![0; capacity.next_power_of_two().max(MIN_BUCKETS)],
+            rekeys: 0,
+            next_rekey_len: 0,
+        }
+    }
+
+    /// Inserts a key-value pair into the map, returning the previous value if the key was present.
+    ///
+    /// If this causes the map to detect a flooding attack, all of the entries are rehashed with a new hasher.
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        let hash = self.map.hasher().hash_one(&k);
+        let previous = self.map.insert(k, v);
+        if previous.is_none() {
+            if self.map.len() > self.bucket_loads.len() {
+                self.recount(self.bucket_loads.len() * 2);
+            } else {
+                let bucket = self.bucket(hash);
+                self.bucket_loads[bucket] += 1;
+                if self.bucket_loads[bucket] > MAX_BUCKET_LOAD {
+                    self.rekey_if_allowed();
+                }
+            }
+        }
+        previous
+    }
+
+    /// Gets the given key's corresponding entry in the map for in-place manipulation.
+    ///
+    /// If the key is not present, room is made for it in the bucket counts first. So if inserting it via the entry
+    /// would cause the map to detect a flooding attack, the entries are rehashed before the entry is returned.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        if !self.map.contains_key(&key) {
+            if self.map.len() + 1 > self.bucket_loads.len() {
+                self.recount(self.bucket_loads.len() * 2);
+            }
+            let bucket = self.bucket(self.map.hasher().hash_one(&key));
+            if self.bucket_loads[bucket] + 1 > MAX_BUCKET_LOAD {
+                self.rekey_if_allowed();
+            }
+        }
+        let bucket = self.bucket(self.map.hasher().hash_one(&key));
+        let load = &mut self.bucket_loads[bucket];
+        match self.map.entry(key) {
+            hash_map::Entry::Occupied(entry) => Entry::Occupied(OccupiedEntry { entry, load }),
+            hash_map::Entry::Vacant(entry) => Entry::Vacant(VacantEntry { entry, load }),
+        }
+    }
+
+    /// Removes a key from the map, returning the value if the key was present.
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let removed = self.map.remove(k);
+        if removed.is_some() {
+            let bucket = self.bucket(self.map.hasher().hash_one(k));
+            self.bucket_loads[bucket] -= 1;
+        }
+        removed
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get_mut(k)
+    }
+
+    /// An iterator visiting all key-value pairs in arbitrary order, with mutable references to the values.
+    ///
+    /// The keys cannot be modified, so this does not affect the bucket counts.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        self.map.iter_mut()
+    }
+
+    /// An iterator visiting all values mutably in arbitrary order.
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        self.map.values_mut()
+    }
+
+    /// Retains only the entries for which `f` returns true.
+    ///
+    /// The bucket counts are recomputed afterwards, which requires hashing each of the remaining keys.
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let len = self.map.len();
+        self.map.retain(f);
+        if self.map.len() != len {
+            let buckets = self.bucket_loads.len();
+            self.recount(buckets);
+        }
+    }
+
+    /// Removes all entries. The hasher is kept.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.bucket_loads.iter_mut().for_each(|load| *load = 0);
+    }
+
+    /// Returns the number of times the entries have been rehashed with a new hasher because flooding was detected.
+    pub fn rekey_count(&self) -> usize {
+        self.rekeys
+    }
+
+    /// Converts this into an ordinary [`AHashMap`].
+    pub fn into_inner(self) -> AHashMap<K, V, S> {
+        self.map
+    }
+
+    #[inline]
+    fn bucket(&self, hash: u64) -> usize {
+        hash as usize & (self.bucket_loads.len() - 1)
+    }
+
+    fn max_bucket_load(&self) -> u32 {
+        self.bucket_loads.iter().copied().max().unwrap_or(0)
+    }
+
+    /// Recomputes the bucket counts, using `buckets` buckets.
+    fn recount(&mut self, buckets: usize) {
+        self.bucket_loads.clear();
+        self.bucket_loads.resize(buckets, 0);
+        let mask = buckets - 1;
+        for key in self.map.keys() {
+            self.bucket_loads[self.map.hasher().hash_one(key) as usize & mask] += 1;
+        }
+        if self.max_bucket_load() > MAX_BUCKET_LOAD {
+            self.rekey_if_allowed();
+        }
+    }
+
+    fn rekey_if_allowed(&mut self) {
+        if self.map.len() < self.next_rekey_len {
+            return;
+        }
+        let len = self.map.len();
+        self.rekeys += 1;
+        self.next_rekey_len = len * 2;
+        let old = mem::replace(&mut self.map, AHashMap::with_capacity(len));
+        self.map.extend(old);
+        let buckets = self.bucket_loads.len();
+        self.recount(buckets);
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for RekeyingAHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    /// Inserts each of the pairs as if by `insert`.
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for RekeyingAHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let iter = iter.into_iter();
+        let mut map = RekeyingAHashMap::with_capacity(iter.size_hint().0);
+        map.extend(iter);
+        map
+    }
+}
+
+/// A view into a single entry in a [`RekeyingAHashMap`], which may either be vacant or occupied.
+///
+/// This is constructed from the [`entry`](RekeyingAHashMap::entry) method. Inserting or removing via the entry
+/// updates the bucket counts of the map.
+pub enum Entry<'a, K, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+/// A view into an occupied entry in a [`RekeyingAHashMap`]. It is part of the [`Entry`] enum.
+pub struct OccupiedEntry<'a, K, V> {
+    entry: hash_map::OccupiedEntry<'a, K, V>,
+    /// The count of the bucket the key's hash selects.
+    load: &'a mut u32,
+}
+
+/// A view into a vacant entry in a [`RekeyingAHashMap`]. It is part of the [`Entry`] enum.
+pub struct VacantEntry<'a, K, V> {
+    entry: hash_map::VacantEntry<'a, K, V>,
+    /// The count of the bucket the key's hash selects.
+    load: &'a mut u32,
+}
+
+impl<'a, K, V> Entry<'a, K, V> {
+    /// Ensures a value is in the entry by inserting the default if empty, and returns a mutable reference to the value.
+    #[inline]
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Ensures a value is in the entry by inserting the result of `default` if empty, and returns a mutable reference
+    /// to the value.
+    #[inline]
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Returns a reference to this entry's key.
+    #[inline]
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Provides in-place mutable access to an occupied entry before any potential inserts into the map.
+    #[inline]
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        match self {
+            Entry::Occupied(mut entry) => {
+                f(entry.get_mut());
+                Entry::Occupied(entry)
+            }
+            Entry::Vacant(entry) => Entry::Vacant(entry),
+        }
+    }
+}
+
+impl<'a, K, V: Default> Entry<'a, K, V> {
+    /// Ensures a value is in the entry by inserting the default value if empty, and returns a mutable reference to the
+    /// value.
+    #[inline]
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    /// Gets a reference to the key in the entry.
+    #[inline]
+    pub fn key(&self) -> &K {
+        self.entry.key()
+    }
+
+    /// Gets a reference to the value in the entry.
+    #[inline]
+    pub fn get(&self) -> &V {
+        self.entry.get()
+    }
+
+    /// Gets a mutable reference to the value in the entry.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut V {
+        self.entry.get_mut()
+    }
+
+    /// Converts the entry into a mutable reference to its value, with the lifetime of the map.
+    #[inline]
+    pub fn into_mut(self) -> &'a mut V {
+        self.entry.into_mut()
+    }
+
+    /// Sets the value of the entry, and returns the entry's old value.
+    #[inline]
+    pub fn insert(&mut self, value: V) -> V {
+        self.entry.insert(value)
+    }
+
+    /// Takes the value out of the entry, and returns it.
+    #[inline]
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    /// Takes the ownership of the key and value from the map.
+    #[inline]
+    pub fn remove_entry(self) -> (K, V) {
+        *self.load -= 1;
+        self.entry.remove_entry()
+    }
+}
+
+impl<'a, K, V> VacantEntry<'a, K, V> {
+    /// Gets a reference to the key that would be used when inserting a value through the entry.
+    #[inline]
+    pub fn key(&self) -> &K {
+        self.entry.key()
+    }
+
+    /// Takes ownership of the key.
+    #[inline]
+    pub fn into_key(self) -> K {
+        self.entry.into_key()
+    }
+
+    /// Sets the value of the entry, and returns a mutable reference to it.
+    #[inline]
+    pub fn insert(self, value: V) -> &'a mut V {
+        *self.load += 1;
+        self.entry.insert(value)
+    }
+}
+
+impl<K, V, S> Deref for RekeyingAHashMap<K, V, S> {
+    type Target = AHashMap<K, V, S>;
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+
+impl<K, V, S> Debug for RekeyingAHashMap<K, V, S>
+where
+    K: Debug,
+    V: Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_map().entries(self.map.iter()).finish()
+    }
+}
+
+impl<K, V, S> Default for RekeyingAHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operations::folded_multiply;
+    use crate::random_state::MULTIPLE;
+    use std::cell::Cell;
+    use std::hash::Hasher;
+
+    thread_local! {
+        // Per thread, so that each test starts from the seed 0.
+        static NEXT_WEAK_SEED: Cell<u64> = const { Cell::new(0) };
+    }
+
+    /// A deliberately weak keyed hasher. Its first instance uses the seed 0, which an attacker is assumed to know, so
+    /// they can choose keys that all land in the same bucket. Later instances are seeded differently.
+    struct WeakState(u64);
+
+    impl Default for WeakState {
+        fn default() -> Self {
+            WeakState(NEXT_WEAK_SEED.with(|next| next.replace(next.get().wrapping_add(0x9E37_79B9_7F4A_7C15))))
+        }
+    }
+
+    impl BuildHasher for WeakState {
+        type Hasher = WeakHasher;
+        fn build_hasher(&self) -> WeakHasher {
+            WeakHasher(self.0, 0)
+        }
+    }
+
+    struct WeakHasher(u64, u64);
+
+    impl Hasher for WeakHasher {
+        fn write(&mut self, bytes: &[u8]) {
+            for byte in bytes {
+                self.1 = self.1.rotate_left(8) ^ *byte as u64;
+            }
+        }
+        fn finish(&self) -> u64 {
+            if self.0 == 0 {
+                // Without a seed the input is returned as is, so it is trivial to find keys that collide.
+                self.1
+            } else {
+                folded_multiply(self.1 ^ self.0, MULTIPLE)
+            }
+        }
+    }
+
+    /// Ignores the keys entirely, so rekeying can't help.
+    #[derive(Default)]
+    struct ConstantState;
+
+    impl BuildHasher for ConstantState {
+        type Hasher = ConstantHasher;
+        fn build_hasher(&self) -> ConstantHasher {
+            ConstantHasher
+        }
+    }
+
+    struct ConstantHasher;
+
+    impl Hasher for ConstantHasher {
+        fn write(&mut self, _bytes: &[u8]) {}
+        fn finish(&self) -> u64 {
+            0
+        }
+    }
+
+    #[test]
+    fn test_no_rekey_with_random_state() {
+        let mut map: RekeyingAHashMap<u64, u64> = RekeyingAHashMap::new();
+        for i in 0..100_000 {
+            map.insert(i, i);
+        }
+        for i in (0..100_000).step_by(2) {
+            assert_eq!(Some(i), map.remove(&i));
+        }
+        assert_eq!(50_000, map.len());
+        assert_eq!(0, map.rekey_count());
+        assert!(map.max_bucket_load() <= MAX_BUCKET_LOAD);
+    }
+
+    #[test]
+    fn test_flooding_triggers_rekey() {
+        let mut map: RekeyingAHashMap<u64, u64, WeakState> = RekeyingAHashMap::new();
+        assert_eq!(0, map.hasher().0);
+        // Without a seed these keys all hash to values whose low bits are zero, so they select the same bucket.
+        let attack_keys: Vec<u64> = (0..2000).map(|i| (i << 16) | 0xBEEF).collect();
+        for key in attack_keys.iter() {
+            map.insert(*key, *key * 2);
+        }
+        assert!(map.rekey_count() >= 1);
+        assert_ne!(0, map.hasher().0);
+        assert!(map.max_bucket_load() <= MAX_BUCKET_LOAD);
+        assert_eq!(attack_keys.len(), map.len());
+        for key in attack_keys.iter() {
+            assert_eq!(Some(&(*key * 2)), map.get(key));
+        }
+    }
+
+    #[test]
+    fn test_rekey_is_limited_when_it_does_not_help() {
+        let mut map: RekeyingAHashMap<u64, u64, ConstantState> = RekeyingAHashMap::new();
+        for i in 0..4096 {
+            map.insert(i, i);
+        }
+        assert_eq!(4096, map.len());
+        // Once when the limit is first exceeded, and then every time the size doubles.
+        assert!(map.rekey_count() <= 8, "rekeyed {} times", map.rekey_count());
+        for i in 0..4096 {
+            assert_eq!(Some(&i), map.get(&i));
+        }
+    }
+
+    #[test]
+    fn test_insert_existing_and_clear() {
+        let mut map: RekeyingAHashMap<&str, u32> = RekeyingAHashMap::default();
+        assert_eq!(None, map.insert("a", 1));
+        assert_eq!(Some(1), map.insert("a", 2));
+        *map.get_mut("a").unwrap() += 1;
+        assert_eq!(Some(&3), map.get("a"));
+        assert_eq!(1, map.bucket_loads.iter().sum::<u32>());
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(0, map.bucket_loads.iter().sum::<u32>());
+        assert_eq!(None, map.remove("a"));
+    }
+
+    #[test]
+    fn test_entry_updates_loads() {
+        let mut map: RekeyingAHashMap<&str, u32> = RekeyingAHashMap::new();
+        *map.entry("a").or_insert(0) += 1;
+        *map.entry("a").or_insert(0) += 1;
+        *map.entry("b").or_default() += 5;
+        assert_eq!(Some(&2), map.get("a"));
+        assert_eq!(Some(&5), map.get("b"));
+        assert_eq!(2, map.bucket_loads.iter().sum::<u32>());
+        match map.entry("a") {
+            Entry::Occupied(entry) => assert_eq!(2, entry.remove()),
+            Entry::Vacant(_) => panic!("a should be present"),
+        }
+        match map.entry("c") {
+            Entry::Occupied(_) => panic!("c should not be present"),
+            Entry::Vacant(entry) => assert_eq!("c", entry.into_key()),
+        }
+        assert_eq!(1, map.len());
+        assert_eq!(1, map.bucket_loads.iter().sum::<u32>());
+    }
+
+    #[test]
+    fn test_flooding_via_entry_triggers_rekey() {
+        let mut map: RekeyingAHashMap<u64, u64, WeakState> = RekeyingAHashMap::new();
+        let attack_keys: Vec<u64> = (0..2000).map(|i| (i << 16) | 0xBEEF).collect();
+        for key in attack_keys.iter() {
+            map.entry(*key).or_insert(*key * 2);
+        }
+        assert!(map.rekey_count() >= 1);
+        assert!(map.max_bucket_load() <= MAX_BUCKET_LOAD);
+        assert_eq!(attack_keys.len() as u32, map.bucket_loads.iter().sum::<u32>());
+        for key in attack_keys.iter() {
+            assert_eq!(Some(&(*key * 2)), map.get(key));
+        }
+    }
+
+    #[test]
+    fn test_extend_and_collect() {
+        let mut map: RekeyingAHashMap<u64, u64, WeakState> = (0..1000_u64).map(|i| ((i << 16) | 0xBEEF, i)).collect();
+        assert!(map.rekey_count() >= 1);
+        map.extend((1000..2000_u64).map(|i| ((i << 16) | 0xBEEF, i)));
+        assert_eq!(2000, map.len());
+        assert_eq!(2000, map.bucket_loads.iter().sum::<u32>());
+        assert!(map.max_bucket_load() <= MAX_BUCKET_LOAD);
+    }
+
+    #[test]
+    fn test_retain_and_iter_mut() {
+        let mut map: RekeyingAHashMap<u64, u64> = (0..1000).map(|i| (i, i)).collect();
+        for (_, v) in map.iter_mut() {
+            *v *= 2;
+        }
+        map.values_mut().for_each(|v| *v += 1);
+        map.retain(|k, _| k % 3 == 0);
+        assert_eq!(334, map.len());
+        assert_eq!(334, map.bucket_loads.iter().sum::<u32>());
+        for (k, v) in map.iter() {
+            assert_eq!(k * 2 + 1, *v);
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Tests that hashers created from related seeds produce unrelated output.
+//!
+//! An attacker who can observe the hashes produced by one instance (for example one created with
+//! `RandomState::with_seeds(k, k + 1)`) should learn nothing about the hashes produced by another instance whose seeds
+//! are related to the first in a simple way. For each family of related seeds, pairs of hashers are created through
+//! `scramble_keys` and used to hash the same inputs, and the outputs are checked for:
+//!
+//! * Correlation between any bit of one output and any bit of the other. (This includes rotated or permuted
+//!   relationships, not only bits at the same position.)
+//! * Differentials: the xor or difference of the two outputs being the same for different inputs, or zero.
+use crate::hash_statistics_test::{bias, bias_limit, test_both_backends, Report};
+use core::hash::Hasher;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Input {
+    Bytes(Vec<u8>),
+    U64(u64),
+}
+
+/// Derives the seeds of a second hasher from those of the first.
+type Related = Box<dyn Fn(u64, u64) -> (u64, u64)>;
+
+fn families() -> Vec<(String, Related)> {
+    let mut families: Vec<(String, Related)> = vec![
+        ("(k0 + 1, k1)".into(), Box::new(|k0: u64, k1| (k0.wrapping_add(1), k1))),
+        ("(k0, k1 + 1)".into(), Box::new(|k0, k1: u64| (k0, k1.wrapping_add(1)))),
+        (
+            "(k0 + 1, k1 + 1)".into(),
+            Box::new(|k0: u64, k1: u64| (k0.wrapping_add(1), k1.wrapping_add(1))),
+        ),
+        // The next seeds in a sequence like `with_seeds(k, k + 1)`.
+        ("(k1, k1 + 1)".into(), Box::new(|_, k1: u64| (k1, k1.wrapping_add(1)))),
+        ("(k1, k0)".into(), Box::new(|k0, k1| (k1, k0))),
+        ("(!k0, k1)".into(), Box::new(|k0: u64, k1| (!k0, k1))),
+        ("(k0 ^ k1, k1)".into(), Box::new(|k0: u64, k1| (k0 ^ k1, k1))),
+        ("(k0.rotate_left(1), k1)".into(), Box::new(|k0: u64, k1| (k0.rotate_left(1), k1))),
+    ];
+    for bit in 0..64 {
+        families.push((
+            format!("(k0 ^ 1 << {}, k1)", bit),
+            Box::new(move |k0, k1| (k0 ^ 1 << bit, k1)),
+        ));
+        families.push((
+            format!("(k0, k1 ^ 1 << {})", bit),
+            Box::new(move |k0, k1| (k0, k1 ^ 1 << bit)),
+        ));
+    }
+    families
+}
+
+/// Distinct random byte strings of various lengths, and random `u64`s. (A repeated input would hash to a repeated
+/// xor and difference, and be counted as a differential)
+fn inputs(count: usize) -> Vec<Input> {
+    let mut rng = StdRng::seed_from_u64(0x1A7ED);
+    let mut seen = HashSet::new();
+    let mut inputs = Vec::with_capacity(count);
+    while inputs.len() < count {
+        let input = if inputs.len() % 4 == 0 {
+            Input::U64(rng.gen())
+        } else {
+            let len = rng.gen_range(0, 100);
+            Input::Bytes((0..len).map(|_| rng.gen()).collect())
+        };
+        if seen.insert(input.clone()) {
+            inputs.push(input);
+        }
+    }
+    inputs
+}
+
+/// The seeds of the first hasher of each pair. Half are random and half are small values, as low entropy seeds are
+/// the most likely to be used for related instances.
+fn base_seeds(count: usize) -> Vec<(u64, u64)> {
+    let mut rng = StdRng::seed_from_u64(0x5EED);
+    (0..count)
+        .map(|i| {
+            if i % 2 == 0 {
+                (rng.gen(), rng.gen())
+            } else {
+                (i as u64 / 2, i as u64 / 2 + 1)
+            }
+        })
+        .collect()
+}
+
+fn hash<T: Hasher>(mut hasher: T, input: &Input) -> u64 {
+    match input {
+        Input::Bytes(bytes) => hasher.write(bytes),
+        Input::U64(value) => hasher.write_u64(*value),
+    }
+    hasher.finish()
+}
+
+fn count_repeats(values: &mut [u64]) -> usize {
+    values.sort_unstable();
+    values.windows(2).filter(|w| w[0] == w[1]).count()
+}
+
+fn test_related_seeds<T: Hasher>(constructor: impl Fn(u64, u64) -> T, seeds: usize, inputs_per_seed: usize) {
+    let families = families();
+    let inputs = inputs(inputs_per_seed);
+    let seeds = base_seeds(seeds);
+    let cells = families.len() * 64 * 64;
+    for (name, related) in families.iter() {
+        // `counts[i * 64 + j]` is the number of times bit `i` of the first output equaled bit `j` of the second.
+        let mut counts = vec![0_u32; 64 * 64];
+        let mut differentials = 0;
+        let mut samples = 0;
+        for (k0, k1) in seeds.iter() {
+            let (r0, r1) = related(*k0, *k1);
+            if (r0, r1) == (*k0, *k1) {
+                // For example rotating 0
+                continue;
+            }
+            samples += inputs.len();
+            let mut xors = Vec::with_capacity(inputs.len());
+            let mut differences = Vec::with_capacity(inputs.len());
+            for input in inputs.iter() {
+                let a = hash(constructor(*k0, *k1), input);
+                let b = hash(constructor(r0, r1), input);
+                for (i, row) in counts.chunks_mut(64).enumerate() {
+                    let equal = !(b ^ ((a >> i) & 1).wrapping_neg());
+                    for (j, count) in row.iter_mut().enumerate() {
+                        *count += ((equal >> j) & 1) as u32;
+                    }
+                }
+                if a == b {
+                    differentials += 1;
+                }
+                xors.push(a ^ b);
+                differences.push(a.wrapping_sub(b));
+            }
+            differentials += count_repeats(&mut xors) + count_repeats(&mut differences);
+        }
+        Report {
+            test: format!("seeds {} bit correlation", name),
+            measure: "bias",
+            worst: counts.iter().map(|c| bias(*c, samples)).fold(0.0, f64::max),
+            // Each family checks the correlation of every pair of output bits, so this accounts for all of them.
+            limit: bias_limit(samples, cells),
+        }
+        .check();
+        Report {
+            test: format!("seeds {} differentials", name),
+            measure: "count",
+            worst: differentials as f64,
+            limit: 0.0,
+        }
+        .check();
+    }
+}
+
+/// A hasher which mixes its input well but only xors in its key at the end, so related keys give related outputs.
+struct KeyXoredAtEnd {
+    key: u64,
+    hash: u64,
+}
+
+impl Hasher for KeyXoredAtEnd {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.hash = (self.hash ^ *byte as u64).wrapping_mul(0x5851_f42d_4c95_7f2d).rotate_left(29);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash ^ self.key
+    }
+}
+
+#[test]
+fn test_inputs_are_distinct() {
+    let inputs = inputs(1000);
+    assert_eq!(inputs.len(), inputs.iter().collect::<HashSet<_>>().len());
+}
+
+#[test]
+#[should_panic(expected = "bit correlation")]
+fn test_weak_keying_is_detected() {
+    test_related_seeds(|k0, k1| KeyXoredAtEnd { key: k0 ^ k1, hash: 0 }, 8, 32);
+}
+
+test_both_backends! {
+    fn related_seeds() {
+        test_related_seeds(AHasher::test_with_keys, 128, 128);
+    }
+}
@@ -0,0 +1,200 @@
+//! A software implementation of a single round of AES, matching the `aesenc` and `aesdec` x86 instructions.
+//!
+//! This allows the AES version of the hash to be computed (much more slowly) on platforms without AES-NI, such as
+//! under miri or on other architectures. It is used by the tests, and by `AHasher` when the `soft-aes` feature is
+//! enabled. It uses lookup tables, so unlike the hardware instruction its timing is dependent on the data being
+//! processed.
+
+const SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+const INV_SBOX: [u8; 256] = [
+    0x52, 0x09, 0x6a, 0xd5, 0x30, 0x36, 0xa5, 0x38, 0xbf, 0x40, 0xa3, 0x9e, 0x81, 0xf3, 0xd7, 0xfb,
+    0x7c, 0xe3, 0x39, 0x82, 0x9b, 0x2f, 0xff, 0x87, 0x34, 0x8e, 0x43, 0x44, 0xc4, 0xde, 0xe9, 0xcb,
+    0x54, 0x7b, 0x94, 0x32, 0xa6, 0xc2, 0x23, 0x3d, 0xee, 0x4c, 0x95, 0x0b, 0x42, 0xfa, 0xc3, 0x4e,
+    0x08, 0x2e, 0xa1, 0x66, 0x28, 0xd9, 0x24, 0xb2, 0x76, 0x5b, 0xa2, 0x49, 0x6d, 0x8b, 0xd1, 0x25,
+    0x72, 0xf8, 0xf6, 0x64, 0x86, 0x68, 0x98, 0x16, 0xd4, 0xa4, 0x5c, 0xcc, 0x5d, 0x65, 0xb6, 0x92,
+    0x6c, 0x70, 0x48, 0x50, 0xfd, 0xed, 0xb9, 0xda, 0x5e, 0x15, 0x46, 0x57, 0xa7, 0x8d, 0x9d, 0x84,
+    0x90, 0xd8, 0xab, 0x00, 0x8c, 0xbc, 0xd3, 0x0a, 0xf7, 0xe4, 0x58, 0x05, 0xb8, 0xb3, 0x45, 0x06,
+    0xd0, 0x2c, 0x1e, 0x8f, 0xca, 0x3f, 0x0f, 0x02, 0xc1, 0xaf, 0xbd, 0x03, 0x01, 0x13, 0x8a, 0x6b,
+    0x3a, 0x91, 0x11, 0x41, 0x4f, 0x67, 0xdc, 0xea, 0x97, 0xf2, 0xcf, 0xce, 0xf0, 0xb4, 0xe6, 0x73,
+    0x96, 0xac, 0x74, 0x22, 0xe7, 0xad, 0x35, 0x85, 0xe2, 0xf9, 0x37, 0xe8, 0x1c, 0x75, 0xdf, 0x6e,
+    0x47, 0xf1, 0x1a, 0x71, 0x1d, 0x29, 0xc5, 0x89, 0x6f, 0xb7, 0x62, 0x0e, 0xaa, 0x18, 0xbe, 0x1b,
+    0xfc, 0x56, 0x3e, 0x4b, 0xc6, 0xd2, 0x79, 0x20, 0x9a, 0xdb, 0xc0, 0xfe, 0x78, 0xcd, 0x5a, 0xf4,
+    0x1f, 0xdd, 0xa8, 0x33, 0x88, 0x07, 0xc7, 0x31, 0xb1, 0x12, 0x10, 0x59, 0x27, 0x80, 0xec, 0x5f,
+    0x60, 0x51, 0x7f, 0xa9, 0x19, 0xb5, 0x4a, 0x0d, 0x2d, 0xe5, 0x7a, 0x9f, 0x93, 0xc9, 0x9c, 0xef,
+    0xa0, 0xe0, 0x3b, 0x4d, 0xae, 0x2a, 0xf5, 0xb0, 0xc8, 0xeb, 0xbb, 0x3c, 0x83, 0x53, 0x99, 0x61,
+    0x17, 0x2b, 0x04, 0x7e, 0xba, 0x77, 0xd6, 0x26, 0xe1, 0x69, 0x14, 0x63, 0x55, 0x21, 0x0c, 0x7d,
+];
+
+/// Multiplies by 2 in GF(2^8).
+#[inline(always)]
+const fn xtime(a: u8) -> u8 {
+    (a << 1) ^ (((a >> 7) & 1) * 0x1b)
+}
+
+/// Multiplies two values in GF(2^8).
+#[inline(always)]
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut result = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            result ^= a;
+        }
+        a = xtime(a);
+        b >>= 1;
+    }
+    result
+}
+
+/// Performs one round of AES encryption (ShiftRows, SubBytes, MixColumns, then xor with the key).
+/// This produces the same output as `_mm_aesenc_si128`.
+#[allow(unused)] // Not used if AES-NI is enabled.
+pub(crate) fn aesenc(value: u128, xor: u128) -> u128 {
+    let state = value.to_le_bytes();
+    let mut result = [0_u8; 16];
+    for column in 0..4 {
+        let mut a = [0_u8; 4];
+        for row in 0..4 {
+            a[row] = SBOX[state[row + 4 * ((column + row) % 4)] as usize];
+        }
+        result[4 * column] = xtime(a[0]) ^ xtime(a[1]) ^ a[1] ^ a[2] ^ a[3];
+        result[4 * column + 1] = a[0] ^ xtime(a[1]) ^ xtime(a[2]) ^ a[2] ^ a[3];
+        result[4 * column + 2] = a[0] ^ a[1] ^ xtime(a[2]) ^ xtime(a[3]) ^ a[3];
+        result[4 * column + 3] = xtime(a[0]) ^ a[0] ^ a[1] ^ a[2] ^ xtime(a[3]);
+    }
+    u128::from_le_bytes(result) ^ xor
+}
+
+/// Performs one round of AES decryption (InvShiftRows, InvSubBytes, InvMixColumns, then xor with the key).
+/// This produces the same output as `_mm_aesdec_si128`.
+#[allow(unused)] // Not used if AES-NI is enabled.
+pub(crate) fn aesdec(value: u128, xor: u128) -> u128 {
+    let state = value.to_le_bytes();
+    let mut result = [0_u8; 16];
+    for column in 0..4 {
+        let mut a = [0_u8; 4];
+        for row in 0..4 {
+            a[row] = INV_SBOX[state[row + 4 * ((column + 4 - row) % 4)] as usize];
+        }
+        result[4 * column] = gf_mul(a[0], 14) ^ gf_mul(a[1], 11) ^ gf_mul(a[2], 13) ^ gf_mul(a[3], 9);
+        result[4 * column + 1] = gf_mul(a[0], 9) ^ gf_mul(a[1], 14) ^ gf_mul(a[2], 11) ^ gf_mul(a[3], 13);
+        result[4 * column + 2] = gf_mul(a[0], 13) ^ gf_mul(a[1], 9) ^ gf_mul(a[2], 14) ^ gf_mul(a[3], 11);
+        result[4 * column + 3] = gf_mul(a[0], 11) ^ gf_mul(a[1], 13) ^ gf_mul(a[2], 9) ^ gf_mul(a[3], 14);
+    }
+    u128::from_le_bytes(result) ^ xor
+}
+
+/// Rearranges the bytes of `value` according to `mask`, producing the same output as `_mm_shuffle_epi8`.
+#[allow(unused)] // Not used if SSSE3 is enabled.
+pub(crate) fn shuffle_bytes(value: u128, mask: u128) -> u128 {
+    let bytes = value.to_le_bytes();
+    let mask = mask.to_le_bytes();
+    let mut result = [0_u8; 16];
+    for i in 0..16 {
+        if mask[i] & 0x80 == 0 {
+            result[i] = bytes[(mask[i] & 0x0f) as usize];
+        }
+    }
+    u128::from_le_bytes(result)
+}
+
+#[cfg(all(test, any(target_arch = "x86", target_arch = "x86_64"), not(miri)))]
+mod tests {
+    use super::*;
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::*;
+    use core::mem::transmute;
+    use rand::{Rng, SeedableRng};
+
+    #[target_feature(enable = "aes")]
+    unsafe fn hardware_aesenc(value: u128, xor: u128) -> u128 {
+        transmute(_mm_aesenc_si128(transmute::<u128, __m128i>(value), transmute::<u128, __m128i>(xor)))
+    }
+
+    #[target_feature(enable = "aes")]
+    unsafe fn hardware_aesdec(value: u128, xor: u128) -> u128 {
+        transmute(_mm_aesdec_si128(transmute::<u128, __m128i>(value), transmute::<u128, __m128i>(xor)))
+    }
+
+    #[target_feature(enable = "ssse3")]
+    unsafe fn hardware_shuffle(value: u128, mask: u128) -> u128 {
+        transmute(_mm_shuffle_epi8(transmute::<u128, __m128i>(value), transmute::<u128, __m128i>(mask)))
+    }
+
+    fn test_values() -> Vec<u128> {
+        let mut values = vec![0, u128::MAX, 0x5252_5252_5252_5252_5252_5252_5252_5252];
+        for bit in 0..128 {
+            values.push(1 << bit);
+            values.push(!(1 << bit));
+        }
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x1234_5678);
+        for _ in 0..1000 {
+            values.push(rng.gen());
+        }
+        values
+    }
+
+    #[test]
+    fn test_sbox_inverse() {
+        for i in 0..256 {
+            assert_eq!(i, INV_SBOX[SBOX[i] as usize] as usize);
+        }
+    }
+
+    #[test]
+    fn test_aesenc_matches_hardware() {
+        if !is_x86_feature_detected!("aes") {
+            return;
+        }
+        let values = test_values();
+        for (i, &value) in values.iter().enumerate() {
+            let xor = values[(i * 7 + 3) % values.len()];
+            assert_eq!(unsafe { hardware_aesenc(value, xor) }, aesenc(value, xor), "{:x} {:x}", value, xor);
+            assert_eq!(unsafe { hardware_aesenc(value, 0) }, aesenc(value, 0), "{:x}", value);
+        }
+    }
+
+    #[test]
+    fn test_aesdec_matches_hardware() {
+        if !is_x86_feature_detected!("aes") {
+            return;
+        }
+        let values = test_values();
+        for (i, &value) in values.iter().enumerate() {
+            let xor = values[(i * 7 + 3) % values.len()];
+            assert_eq!(unsafe { hardware_aesdec(value, xor) }, aesdec(value, xor), "{:x} {:x}", value, xor);
+            assert_eq!(unsafe { hardware_aesdec(value, 0) }, aesdec(value, 0), "{:x}", value);
+        }
+    }
+
+    #[test]
+    fn test_shuffle_matches_hardware() {
+        if !is_x86_feature_detected!("ssse3") {
+            return;
+        }
+        let values = test_values();
+        for (i, &value) in values.iter().enumerate() {
+            let mask = values[(i * 7 + 3) % values.len()];
+            assert_eq!(unsafe { hardware_shuffle(value, mask) }, shuffle_bytes(value, mask), "{:x} {:x}", value, mask);
+        }
+    }
+}
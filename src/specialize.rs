@@ -1,5 +1,7 @@
 #[cfg(feature = "specialize")]
 use crate::HasherExt;
+#[cfg(feature = "specialize")]
+use crate::{HashableF32, HashableF64};
 use core::hash::Hash;
 use core::hash::Hasher;
 
@@ -65,6 +67,22 @@ call_hasher_impl!(i16);
 call_hasher_impl!(i32);
 call_hasher_impl!(i64);
 
+#[cfg(feature = "specialize")]
+impl CallHasher for HashableF32 {
+    #[inline]
+    fn get_hash<H: Hasher>(&self, hasher: H) -> u64 {
+        hasher.hash_u64(self.to_canonical_bits() as u64)
+    }
+}
+
+#[cfg(feature = "specialize")]
+impl CallHasher for HashableF64 {
+    #[inline]
+    fn get_hash<H: Hasher>(&self, hasher: H) -> u64 {
+        hasher.hash_u64(self.to_canonical_bits())
+    }
+}
+
 #[cfg(feature = "specialize")]
 impl CallHasher for u128 {
     #[inline]
@@ -119,6 +137,14 @@ impl CallHasher for String {
     }
 }
 
+#[cfg(feature = "specialize")]
+impl<T: CallHasher + ?Sized> CallHasher for &T {
+    #[inline]
+    fn get_hash<H: Hasher>(&self, hasher: H) -> u64 {
+        (**self).get_hash(hasher)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -133,6 +159,23 @@ mod test {
         assert_ne!(hasher.finish(), shortened);
     }
 
+    #[test]
+    #[cfg(feature = "specialize")]
+    pub fn test_specialized_through_reference() {
+        let hasher = || AHasher::new_with_keys(1, 2);
+        assert_eq!("test".get_hash(hasher()), (&"test").get_hash(hasher()));
+        assert_eq!(7_u64.get_hash(hasher()), (&7_u64).get_hash(hasher()));
+    }
+
+    #[test]
+    #[cfg(feature = "specialize")]
+    pub fn test_specialized_floats() {
+        let hasher = || AHasher::new_with_keys(1, 2);
+        assert_eq!(0_u64.get_hash(hasher()), HashableF64(-0.0).get_hash(hasher()));
+        assert_eq!(f64::NAN.to_bits().get_hash(hasher()), HashableF64(-f64::NAN).get_hash(hasher()));
+        assert_eq!((1.5_f32.to_bits() as u64).get_hash(hasher()), HashableF32(1.5).get_hash(hasher()));
+    }
+
     /// Tests that some non-trivial transformation takes place.
     #[test]
     pub fn test_input_processed() {
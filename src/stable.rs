@@ -0,0 +1,24 @@
+//! Versions of aHash whose output is fixed.
+//!
+//! The output of [AHasher](crate::AHasher) depends on the CPU features available, and is allowed to change
+//! between versions of this crate. This makes it unsuitable for hashes that are persisted or shared between processes.
+//!
+//! The hashers in this module produce the same output on every platform, regardless of endianness,
+//! pointer width, enabled target features or crate features. Once released the output of a given version
+//! will never change. If the algorithm needs to be improved, it will be done by adding a new version.
+//!
+//! These hashers are somewhat slower than [AHasher](crate::AHasher) and should only be used where a stable
+//! output is required.
+//!
+//! # Example
+//!
+//! ```
+//! use ahash::stable::v1::AHasher;
+//! use std::hash::Hasher;
+//!
+//! let mut hasher = AHasher::new_with_keys(1234, 5678);
+//! hasher.write(b"Stored on disk");
+//! let hash = hasher.finish();
+//! ```
+
+pub mod v1;
@@ -0,0 +1,412 @@
+use crate::convert::*;
+use crate::operations::folded_multiply;
+use crate::RandomState;
+use core::hash::Hasher;
+
+const MULTIPLE: u64 = 6364136223846793005;
+const ROT: u32 = 23;
+
+/// Fixed keys used by `AHasher::default()`. (From PCG-64)
+const DEFAULT_KEYS: [u64; 2] = [0x2360_ED05_1FC6_5DA4, 0x4385_DF64_9FCC_F645];
+
+/// A `Hasher` whose output is the same on every platform and will not change in future versions of this crate.
+///
+/// This is a frozen copy of the fallback algorithm, where all data is interpreted as little endian.
+/// `write_usize` is treated the same as `write_u64` so that the output does not depend on the pointer width.
+///
+/// Note that only the output of the `Hasher` methods is guaranteed. Values hashed via their `Hash` implementation
+/// are only stable as long as that implementation does not change. (Which is not guaranteed for types outside of
+/// this crate, including those in the standard library.) Where this matters the data should be passed to
+/// `write` or the `write_*` methods directly.
+///
+/// # Example
+///
+/// ```
+/// use ahash::stable::v1::AHasher;
+/// use std::hash::Hasher;
+///
+/// let mut hasher = AHasher::new_with_keys(1234, 5678);
+/// hasher.write_u32(1989);
+/// hasher.write(b"Huh?");
+///
+/// println!("Hash is {:x}!", hasher.finish());
+/// ```
+#[derive(Debug, Clone)]
+pub struct AHasher {
+    buffer: u64,
+    pad: u64,
+    extra_keys: [u64; 2],
+}
+
+impl AHasher {
+    /// Creates a new hasher keyed to the provided keys.
+    ///
+    /// Hashes produced with different keys will be completely different. To reproduce a hash the same keys must be
+    /// supplied.
+    #[inline]
+    pub const fn new_with_keys(key1: u128, key2: u128) -> AHasher {
+        let extra = key1 ^ key2;
+        AHasher {
+            buffer: key1 as u64,
+            pad: key2 as u64,
+            extra_keys: [extra as u64, (extra >> 64) as u64],
+        }
+    }
+
+    /// Creates a new hasher with the same keys as the hashers built by `state`.
+    ///
+    /// Note that the output is still that of this algorithm, and not the same as [crate::AHasher].
+    #[inline]
+    pub const fn from_random_state(state: &RandomState) -> AHasher {
+        AHasher::new_with_keys(
+            (state.k0 as u128) | ((state.k1 as u128) << 64),
+            (state.k2 as u128) | ((state.k3 as u128) << 64),
+        )
+    }
+
+    /// The equivalent of `write` followed by `finish`, which can be evaluated at compile time.
+    const fn const_write_finish(self, data: &[u8]) -> u64 {
+        let len = data.len();
+        let mut buffer = self.buffer.wrapping_add(len as u64).wrapping_mul(MULTIPLE);
+        if len > 8 {
+            if len > 16 {
+                buffer = self.const_large_update(buffer, read_le(data, len - 16, 8), read_le(data, len - 8, 8));
+                let mut offset = 0;
+                while len - offset > 16 {
+                    buffer = self.const_large_update(buffer, read_le(data, offset, 8), read_le(data, offset + 8, 8));
+                    offset += 16;
+                }
+            } else {
+                buffer = self.const_large_update(buffer, read_le(data, 0, 8), read_le(data, len - 8, 8));
+            }
+        } else if len >= 2 {
+            if len >= 4 {
+                buffer = self.const_large_update(buffer, read_le(data, 0, 4), read_le(data, len - 4, 4));
+            } else {
+                let value = read_le(data, 0, 2) | ((data[len - 1] as u64) << 32);
+                buffer = folded_multiply(value ^ buffer, MULTIPLE);
+            }
+        } else if len == 1 {
+            buffer = folded_multiply(data[0] as u64 ^ buffer, MULTIPLE);
+        }
+        let rot = (buffer & 63) as u32;
+        folded_multiply(buffer, self.pad).rotate_left(rot)
+    }
+
+    /// The same as `large_update`, but returns the new buffer instead.
+    const fn const_large_update(&self, buffer: u64, low: u64, high: u64) -> u64 {
+        let combined = folded_multiply(low ^ self.extra_keys[0], high ^ self.extra_keys[1]);
+        (self.pad.wrapping_add(combined) ^ buffer).rotate_left(ROT)
+    }
+
+    #[inline(always)]
+    fn update(&mut self, new_data: u64) {
+        self.buffer = folded_multiply(new_data ^ self.buffer, MULTIPLE);
+    }
+
+    #[inline(always)]
+    fn large_update(&mut self, low: u64, high: u64) {
+        let combined = folded_multiply(low ^ self.extra_keys[0], high ^ self.extra_keys[1]);
+        self.buffer = (self.pad.wrapping_add(combined) ^ self.buffer).rotate_left(ROT);
+    }
+}
+
+/// Reads `count` bytes starting at `offset` as a little endian integer.
+const fn read_le(data: &[u8], offset: usize, count: usize) -> u64 {
+    let mut result = 0;
+    let mut i = 0;
+    while i < count {
+        result |= (data[offset + i] as u64) << (8 * i);
+        i += 1;
+    }
+    result
+}
+
+/// Hashes `data` using the keys of `state`. As this is a `const fn`, it can be used to compute hashes at compile time.
+///
+/// The result is the same as writing `data` to [AHasher::from_random_state] with a single call to `write`, followed
+/// by `finish`. Because this uses the portable algorithm in this module, it is not the same as hashing the data with
+/// [crate::AHasher] or [crate::RandomState::hash_one].
+///
+/// # Example
+///
+/// ```
+/// use ahash::stable::v1::AHasher;
+/// use ahash::{const_hash, RandomState};
+/// use std::hash::Hasher;
+///
+/// const SEEDS: RandomState = RandomState::with_seeds(1234, 5678);
+/// const GET: u64 = const_hash(b"GET", &SEEDS);
+///
+/// let mut hasher = AHasher::from_random_state(&SEEDS);
+/// hasher.write(b"GET");
+/// assert_eq!(GET, hasher.finish());
+/// ```
+pub const fn const_hash(data: &[u8], state: &RandomState) -> u64 {
+    AHasher::from_random_state(state).const_write_finish(data)
+}
+
+/// The same as [const_hash], but for a `str`.
+pub const fn const_hash_str(data: &str, state: &RandomState) -> u64 {
+    const_hash(data.as_bytes(), state)
+}
+
+impl Default for AHasher {
+    /// Constructs a new [AHasher] with fixed keys.
+    /// Unlike [crate::AHasher] these do not depend on the `compile-time-rng` feature.
+    ///
+    /// Because the keys are publicly known, this should only be used where the input is trusted.
+    #[inline]
+    fn default() -> AHasher {
+        AHasher::new_with_keys(DEFAULT_KEYS[0] as u128, DEFAULT_KEYS[1] as u128)
+    }
+}
+
+/// Provides methods to hash all of the primitive types.
+impl Hasher for AHasher {
+    #[inline]
+    fn write_u8(&mut self, i: u8) {
+        self.update(i as u64);
+    }
+
+    #[inline]
+    fn write_u16(&mut self, i: u16) {
+        self.update(i as u64);
+    }
+
+    #[inline]
+    fn write_u32(&mut self, i: u32) {
+        self.update(i as u64);
+    }
+
+    #[inline]
+    fn write_u64(&mut self, i: u64) {
+        self.update(i);
+    }
+
+    #[inline]
+    fn write_u128(&mut self, i: u128) {
+        self.update(i as u64);
+        self.update((i >> 64) as u64);
+    }
+
+    #[inline]
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    #[inline]
+    #[allow(clippy::collapsible_if)]
+    fn write(&mut self, input: &[u8]) {
+        let mut data = input;
+        let length = data.len() as u64;
+        self.buffer = self.buffer.wrapping_add(length).wrapping_mul(MULTIPLE);
+        if data.len() > 8 {
+            if data.len() > 16 {
+                let len = data.len();
+                let low = u64::from_le_bytes(*as_array!(&data[len - 16..len - 8], 8));
+                let high = u64::from_le_bytes(*as_array!(&data[len - 8..], 8));
+                self.large_update(low, high);
+                while data.len() > 16 {
+                    let (low, rest) = data.read_u64();
+                    let (high, rest) = rest.read_u64();
+                    self.large_update(u64::from_le(low), u64::from_le(high));
+                    data = rest;
+                }
+            } else {
+                self.large_update(u64::from_le(data.read_u64().0), u64::from_le(data.read_last_u64()));
+            }
+        } else {
+            if data.len() >= 2 {
+                if data.len() >= 4 {
+                    let low = u32::from_le(data.read_u32().0) as u64;
+                    let high = u32::from_le(data.read_last_u32()) as u64;
+                    self.large_update(low, high);
+                } else {
+                    let low = u16::from_le(data.read_u16().0) as u64;
+                    let high = data[data.len() - 1] as u64;
+                    self.update(low | (high << 32));
+                }
+            } else {
+                if !data.is_empty() {
+                    self.update(data[0] as u64);
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        let rot = (self.buffer & 63) as u32;
+        folded_multiply(self.buffer, self.pad).rotate_left(rot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ\
+                                !@#$%^&*()-_=+[]{};:,.<>/?~0123456789012";
+
+    fn hash_bytes(hasher: AHasher, len: usize) -> u64 {
+        let mut hasher = hasher;
+        hasher.write(&INPUT[..len]);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_golden_values_bytes() {
+        let lengths = [0, 1, 2, 3, 4, 7, 8, 9, 15, 16, 17, 31, 32, 33, 63, 64, 65, 100];
+        let actual: Vec<u64> = lengths
+            .iter()
+            .map(|&len| hash_bytes(AHasher::new_with_keys(1234, 5678), len))
+            .collect();
+        assert_eq!(actual, GOLDEN_BYTES.to_vec());
+    }
+
+    #[test]
+    fn test_golden_values_default_keys() {
+        let lengths = [0, 3, 8, 16, 33];
+        let actual: Vec<u64> = lengths.iter().map(|&len| hash_bytes(AHasher::default(), len)).collect();
+        assert_eq!(actual, GOLDEN_DEFAULT.to_vec());
+    }
+
+    #[test]
+    fn test_golden_values_primitives() {
+        let keys = (0x0123_4567_89AB_CDEF_FEDC_BA98_7654_3210, 0x0F1E_2D3C_4B5A_6978_8796_A5B4_C3D2_E1F0);
+        let mut actual = Vec::new();
+        let mut hasher = AHasher::new_with_keys(keys.0, keys.1);
+        hasher.write_u8(0xAB);
+        actual.push(hasher.finish());
+        let mut hasher = AHasher::new_with_keys(keys.0, keys.1);
+        hasher.write_u16(0xABCD);
+        actual.push(hasher.finish());
+        let mut hasher = AHasher::new_with_keys(keys.0, keys.1);
+        hasher.write_u32(0xABCD_EF01);
+        actual.push(hasher.finish());
+        let mut hasher = AHasher::new_with_keys(keys.0, keys.1);
+        hasher.write_u64(0xABCD_EF01_2345_6789);
+        actual.push(hasher.finish());
+        let mut hasher = AHasher::new_with_keys(keys.0, keys.1);
+        hasher.write_u128(0xABCD_EF01_2345_6789_0011_2233_4455_6677);
+        actual.push(hasher.finish());
+        let mut hasher = AHasher::new_with_keys(keys.0, keys.1);
+        hasher.write_usize(12345);
+        actual.push(hasher.finish());
+        let mut hasher = AHasher::new_with_keys(keys.0, keys.1);
+        hasher.write_u64(1);
+        hasher.write(b"abc");
+        hasher.write_u32(2);
+        hasher.write(b"0123456789abcdefghij");
+        actual.push(hasher.finish());
+        assert_eq!(actual, GOLDEN_PRIMITIVES.to_vec());
+    }
+
+    #[test]
+    fn test_golden_values_long_inputs() {
+        // Every byte differs, so these fail if the words of the last 16 bytes are not read as little endian.
+        let state = RandomState::with_keys(1, 2, 3, 4);
+        let data: Vec<u8> = (0..100).collect();
+        for (&len, &expected) in [17, 24, 31, 40, 100].iter().zip(GOLDEN_LONG.iter()) {
+            let mut hasher = AHasher::from_random_state(&state);
+            hasher.write(&data[..len]);
+            assert_eq!(expected, hasher.finish(), "{}", len);
+            assert_eq!(expected, const_hash(&data[..len], &state), "{}", len);
+        }
+    }
+
+    #[test]
+    fn test_usize_is_u64() {
+        let mut a = AHasher::new_with_keys(1, 2);
+        let mut b = AHasher::new_with_keys(1, 2);
+        a.write_usize(42);
+        b.write_u64(42);
+        assert_eq!(a.finish(), b.finish());
+    }
+
+    #[test]
+    fn test_const_hash_matches_write() {
+        let data: Vec<u8> = (0..200_u32).map(|i| (i * 113 % 256) as u8).collect();
+        let states = [
+            RandomState::with_seeds(1, 2),
+            RandomState::with_keys(u64::MAX, 0, 3, 1 << 63),
+        ];
+        for state in states.iter() {
+            for start in 0..3 {
+                for end in start..data.len() {
+                    let mut hasher = AHasher::from_random_state(state);
+                    hasher.write(&data[start..end]);
+                    assert_eq!(
+                        hasher.finish(),
+                        const_hash(&data[start..end], state),
+                        "{}..{}",
+                        start,
+                        end
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_const_hash_in_const_context() {
+        const STATE: RandomState = RandomState::with_seeds(1234, 5678);
+        const HASHES: [u64; 3] = [
+            const_hash(b"GET", &STATE),
+            const_hash_str("POST", &STATE),
+            const_hash(b"0123456789abcdefghijklmnopqrstuvwxyz", &STATE),
+        ];
+        assert_eq!(HASHES[0], const_hash(b"GET", &STATE));
+        assert_eq!(HASHES[1], const_hash(b"POST", &STATE));
+        let mut hasher = AHasher::from_random_state(&STATE);
+        hasher.write(b"0123456789abcdefghijklmnopqrstuvwxyz");
+        assert_eq!(HASHES[2], hasher.finish());
+        assert_ne!(HASHES[0], HASHES[1]);
+    }
+
+    // These values must never change. If they do, the change must be made in a new version instead.
+    const GOLDEN_BYTES: [u64; 18] = [
+        0xAD28_E238_B907_7BDC,
+        0x34E4_BB33_9CFD_2907,
+        0xD253_8964_809B_6F3E,
+        0x7E73_15BB_DB62_7F50,
+        0x48F0_E83C_3F48_4E23,
+        0x1ED1_F412_2D63_2310,
+        0x3B6C_C941_9D83_D836,
+        0x8D89_E403_42D4_94F2,
+        0xEA1E_D8CF_5D17_CB02,
+        0x2E15_7F40_B9CE_5F04,
+        0x663E_5EA8_F79C_747D,
+        0x51FA_EDBF_0A9B_6E63,
+        0x80A2_9B19_C71C_F03D,
+        0xE848_31C7_8046_4112,
+        0x1BE0_0156_CEC3_77C7,
+        0x3F90_B48B_4E50_0EB1,
+        0x5515_DD6B_0780_9D87,
+        0x929A_9AFF_D130_0C60,
+    ];
+    const GOLDEN_DEFAULT: [u64; 5] = [
+        0x2952_9A9F_6CF7_63B5,
+        0xF392_B490_76FF_9E1D,
+        0x99D7_5E52_D4B8_9CFC,
+        0x4513_C15B_25CD_05E8,
+        0xDDC0_0328_1B8E_AF64,
+    ];
+    const GOLDEN_LONG: [u64; 5] = [
+        0x2E0C_D692_8716_84F6,
+        0xB6E5_A253_AA59_6CE4,
+        0x3198_6CD2_CEE4_A3B0,
+        0xBC0E_26A9_8144_F4A1,
+        0x7148_A95C_F515_E3C6,
+    ];
+    const GOLDEN_PRIMITIVES: [u64; 7] = [
+        0x56DF_10FA_B840_AA14,
+        0xD06A_0687_8337_2392,
+        0x5E77_5786_DD84_C6EC,
+        0x0DE1_6070_C73C_B87A,
+        0x5FA9_0557_599B_97F7,
+        0x88E4_23FF_C806_FA39,
+        0xCBB2_6726_D1B7_DF7C,
+    ];
+}
@@ -0,0 +1,255 @@
+use crate::AHasher;
+use core::fmt::Debug;
+use core::hash::Hasher;
+
+const BLOCK_SIZE: usize = 64;
+
+/// Exposes the loop each hasher uses to hash long inputs, so that it can be run over data that arrives in pieces.
+pub(crate) trait BlockHasher: Hasher + Clone {
+    type Blocks: Clone + Debug;
+
+    /// Returns the state of the loop before any blocks have been processed.
+    fn start_blocks(&self) -> Self::Blocks;
+
+    /// Processes `data`, which must be a multiple of 64 bytes long.
+    fn update_blocks(&self, blocks: &mut Self::Blocks, data: &[u8]);
+
+    /// Processes the last 64 bytes of the input (which may overlap with the last block processed) and returns a hasher
+    /// which `finish` can be called on. `length` is the total length of the input.
+    fn finish_blocks(&self, blocks: Self::Blocks, tail: &[u8; BLOCK_SIZE], length: u64) -> Self;
+}
+
+/// A hasher for content that arrives in pieces, where the result only depends on the bytes written, and not how they
+/// were divided between calls to `write`.
+///
+/// This is unlike [AHasher], which hashes the length of each call to `write` along with the data. So
+/// writing `"ab"` and then `"cd"` to an [AHasher] produces a different hash than writing `"abcd"`, while
+/// with an `AHashStream` they are the same.
+///
+/// Data is buffered into 64 byte blocks which are processed using the same loop [AHasher] uses for long inputs.
+/// If no more than 64 bytes are written in total, the result is the same as a single call to `write` on an [AHasher]
+/// with the same keys. Longer inputs produce a different (but equally well distributed) result than [AHasher].
+///
+/// Because the other methods on [Hasher] are implemented in terms of `write`, this is intended for hashing byte
+/// content. It is slower than [AHasher] for small keys in a `HashMap`.
+///
+/// # Example
+///
+/// ```
+/// use ahash::AHashStream;
+/// use std::hash::Hasher;
+///
+/// let mut in_pieces = AHashStream::new_with_keys(1234, 5678);
+/// in_pieces.write(b"Hello, ");
+/// in_pieces.write(b"world!");
+///
+/// let mut at_once = AHashStream::new_with_keys(1234, 5678);
+/// at_once.write(b"Hello, world!");
+///
+/// assert_eq!(in_pieces.finish(), at_once.finish());
+/// ```
+#[derive(Debug, Clone)]
+pub struct AHashStream {
+    hasher: AHasher,
+    blocks: <AHasher as BlockHasher>::Blocks,
+    /// Data that has not been processed yet. (Only full blocks which are known not to be the last are processed)
+    buffer: [u8; BLOCK_SIZE],
+    buffered: usize,
+    /// The last block that was processed. Needed to assemble the last 64 bytes of the input.
+    previous: [u8; BLOCK_SIZE],
+    length: u64,
+}
+
+impl AHashStream {
+    /// Creates a new stream keyed to the provided keys. (See `AHasher::new_with_keys`)
+    #[inline]
+    pub fn new_with_keys(key1: u128, key2: u128) -> AHashStream {
+        AHashStream::from_hasher(AHasher::new_with_keys(key1, key2))
+    }
+
+    /// Creates a stream with the same keys as the provided hasher, which must not have had any data written to it.
+    #[inline]
+    pub(crate) fn from_hasher(hasher: AHasher) -> AHashStream {
+        AHashStream {
+            blocks: hasher.start_blocks(),
+            hasher,
+            buffer: [0; BLOCK_SIZE],
+            buffered: 0,
+            previous: [0; BLOCK_SIZE],
+            length: 0,
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, data: &[u8]) {
+        self.hasher.update_blocks(&mut self.blocks, data);
+        self.previous.copy_from_slice(&data[data.len() - BLOCK_SIZE..]);
+    }
+
+    /// Returns the full 128 bit hash of the data written so far. The lower 64 bits are the same as the value returned
+    /// by `finish()`.
+    #[inline]
+    pub fn finish_u128(&self) -> u128 {
+        if self.length <= BLOCK_SIZE as u64 {
+            let mut hasher = self.hasher.clone();
+            hasher.write(&self.buffer[..self.buffered]);
+            return hasher.finish_u128();
+        }
+        let mut tail = [0_u8; BLOCK_SIZE];
+        let (from_previous, from_buffer) = tail.split_at_mut(BLOCK_SIZE - self.buffered);
+        from_previous.copy_from_slice(&self.previous[self.buffered..]);
+        from_buffer.copy_from_slice(&self.buffer[..self.buffered]);
+        self.hasher
+            .finish_blocks(self.blocks.clone(), &tail, self.length)
+            .finish_u128()
+    }
+}
+
+impl Hasher for AHashStream {
+    #[inline]
+    fn write(&mut self, input: &[u8]) {
+        let mut data = input;
+        self.length = self.length.wrapping_add(data.len() as u64);
+        loop {
+            if self.buffered == BLOCK_SIZE && !data.is_empty() {
+                let buffer = self.buffer;
+                self.process(&buffer);
+                self.buffered = 0;
+            }
+            if self.buffered == 0 && data.len() > BLOCK_SIZE {
+                // Process all but the last (possibly partial) block directly from the input.
+                let whole_blocks = (data.len() - 1) / BLOCK_SIZE * BLOCK_SIZE;
+                let (blocks, rest) = data.split_at(whole_blocks);
+                self.process(blocks);
+                data = rest;
+            }
+            let count = (BLOCK_SIZE - self.buffered).min(data.len());
+            self.buffer[self.buffered..self.buffered + count].copy_from_slice(&data[..count]);
+            self.buffered += count;
+            data = &data[count..];
+            if data.is_empty() {
+                return;
+            }
+        }
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.finish_u128() as u64
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Write for AHashStream {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        Hasher::write(self, buf);
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RandomState;
+    use core::hash::BuildHasher;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use proptest::sample::Index;
+
+    fn hash_in_pieces(state: &RandomState, pieces: &[&[u8]]) -> u128 {
+        let mut stream = state.build_stream();
+        for piece in pieces {
+            stream.write(piece);
+        }
+        stream.finish_u128()
+    }
+
+    proptest! {
+        /// For any data split at any points, the result equals hashing the concatenation at once.
+        #[test]
+        fn test_chunking_does_not_matter(
+            seeds in any::<(u64, u64)>(),
+            data in vec(any::<u8>(), 0..2000),
+            splits in vec(any::<Index>(), 0..64),
+        ) {
+            let state = RandomState::with_seeds(seeds.0, seeds.1);
+            let mut points: Vec<usize> = splits.iter().map(|split| split.index(data.len() + 1)).collect();
+            points.sort_unstable();
+            let mut pieces: Vec<&[u8]> = Vec::new();
+            let mut start = 0;
+            for point in points {
+                pieces.push(&data[start..point]);
+                start = point;
+            }
+            pieces.push(&data[start..]);
+            prop_assert_eq!(hash_in_pieces(&state, &[&data]), hash_in_pieces(&state, &pieces));
+        }
+    }
+
+    #[test]
+    fn test_short_input_matches_hasher() {
+        let state = RandomState::with_seeds(1, 2);
+        let data: Vec<u8> = (0..BLOCK_SIZE as u8).collect();
+        for len in 0..=BLOCK_SIZE {
+            let mut hasher = state.build_hasher();
+            hasher.write(&data[..len]);
+            let mut stream = state.build_stream();
+            stream.write(&data[..len / 2]);
+            stream.write(&data[len / 2..len]);
+            assert_eq!(hasher.finish_u128(), stream.finish_u128());
+            assert_eq!(hasher.finish(), stream.finish());
+        }
+    }
+
+    #[test]
+    fn test_each_byte_matters() {
+        let state = RandomState::with_seeds(1, 2);
+        for &len in [65, 127, 128, 129, 300].iter() {
+            let data = vec![0x5A_u8; len];
+            let base = hash_in_pieces(&state, &[&data]);
+            for pos in 0..len {
+                let mut changed = data.clone();
+                changed[pos] ^= 1;
+                assert_ne!(base, hash_in_pieces(&state, &[&changed]), "len {} pos {}", len, pos);
+            }
+        }
+    }
+
+    #[test]
+    fn test_length_matters() {
+        let state = RandomState::with_seeds(1, 2);
+        let data = vec![0_u8; 1000];
+        let mut hashes: Vec<u128> = (0..data.len())
+            .map(|len| hash_in_pieces(&state, &[&data[..len]]))
+            .collect();
+        hashes.sort_unstable();
+        hashes.dedup();
+        assert_eq!(data.len(), hashes.len());
+    }
+
+    #[test]
+    fn test_keys_matter() {
+        let data = [7_u8; 200];
+        let a = hash_in_pieces(&RandomState::with_seeds(1, 2), &[&data]);
+        let b = hash_in_pieces(&RandomState::with_seeds(1, 3), &[&data]);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_finish_does_not_consume() {
+        let state = RandomState::with_seeds(1, 2);
+        let mut stream = state.build_stream();
+        stream.write(&[1; 100]);
+        let first = stream.finish();
+        assert_eq!(first, stream.finish());
+        stream.write(&[2; 100]);
+        assert_ne!(first, stream.finish());
+        assert_eq!(hash_in_pieces(&state, &[&[1; 100], &[2; 100]]) as u64, stream.finish());
+    }
+}
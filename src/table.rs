@@ -0,0 +1,536 @@
+//! An open addressing hash table used to implement `AHashMap` and `AHashSet` when `std` is not available, and to
+//! index the entries of `AHashIndexMap`.
+//!
+//! Values are stored in a power of two sized array along with their hash and found by linear probing from the slot
+//! selected by the low bits of the hash. Removal shifts the following entries back rather than leaving tombstones,
+//! so the probe sequence of every entry is always a contiguous run of occupied slots starting at its ideal slot.
+//! The table never uses more than 5/8ths of its slots, so every probe sequence ends in an empty slot, and (as the
+//! expected length of a linear probe sequence grows quadratically with the load) the sequences stay short.
+//!
+//! The table does not know how to hash values. Callers compute the hash and pass it in along with a predicate to
+//! identify the value they are looking for.
+
+use alloc::collections::TryReserveError;
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+use core::{mem, slice};
+
+/// The smallest number of slots allocated.
+const MIN_SLOTS: usize = 8;
+
+#[derive(Clone)]
+pub(crate) struct Bucket<T> {
+    hash: u64,
+    value: T,
+}
+
+#[derive(Clone)]
+pub(crate) struct RawTable<T> {
+    slots: Vec<Option<Bucket<T>>>,
+    len: usize,
+}
+
+/// Returns the number of slots needed to hold `capacity` values, or `None` if this overflows.
+#[inline]
+fn slots_for(capacity: usize) -> Option<usize> {
+    if capacity == 0 {
+        return Some(0);
+    }
+    let adjusted = capacity.checked_mul(8)?.checked_add(4)? / 5;
+    Some(adjusted.checked_next_power_of_two()?.max(MIN_SLOTS))
+}
+
+impl<T> RawTable<T> {
+    #[inline]
+    pub(crate) const fn new() -> Self {
+        RawTable {
+            slots: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        let mut table = Self::new();
+        table.reserve(capacity);
+        table
+    }
+
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub(crate) fn capacity(&self) -> usize {
+        self.slots.len() / 8 * 5
+    }
+
+    #[inline]
+    fn mask(&self) -> usize {
+        self.slots.len().wrapping_sub(1)
+    }
+
+    /// Returns the index of the value with the given hash that `eq` returns true for.
+    #[inline]
+    pub(crate) fn find(&self, hash: u64, mut eq: impl FnMut(&T) -> bool) -> Option<usize> {
+        if self.len == 0 {
+            return None;
+        }
+        let mask = self.mask();
+        let mut index = hash as usize & mask;
+        while let Some(bucket) = &self.slots[index] {
+            if bucket.hash == hash && eq(&bucket.value) {
+                return Some(index);
+            }
+            index = (index + 1) & mask;
+        }
+        None
+    }
+
+    /// Returns the value at an index previously returned by `find` or `insert`.
+    #[inline]
+    pub(crate) fn get(&self, index: usize) -> &T {
+        &self.slots[index].as_ref().expect("Index of empty slot").value
+    }
+
+    #[inline]
+    pub(crate) fn get_mut(&mut self, index: usize) -> &mut T {
+        &mut self.slots[index].as_mut().expect("Index of empty slot").value
+    }
+
+    /// Adds a value (which must not already be present) and returns its index.
+    #[inline]
+    pub(crate) fn insert(&mut self, hash: u64, value: T) -> usize {
+        self.reserve(1);
+        self.len += 1;
+        self.place(Bucket { hash, value })
+    }
+
+    /// Puts the bucket in the first empty slot of its probe sequence. Does not update `len`.
+    #[inline]
+    fn place(&mut self, bucket: Bucket<T>) -> usize {
+        let mask = self.mask();
+        let mut index = bucket.hash as usize & mask;
+        while self.slots[index].is_some() {
+            index = (index + 1) & mask;
+        }
+        self.slots[index] = Some(bucket);
+        index
+    }
+
+    /// Removes and returns the value at `index`, shifting back any values after it which would no longer be found.
+    pub(crate) fn remove(&mut self, index: usize) -> T {
+        let removed = self.slots[index].take().expect("Index of empty slot");
+        self.len -= 1;
+        let mask = self.mask();
+        let mut hole = index;
+        let mut next = (index + 1) & mask;
+        while let Some(bucket) = &self.slots[next] {
+            let ideal = bucket.hash as usize & mask;
+            // The value can fill the hole if the hole is between its ideal slot and where it is now.
+            if next.wrapping_sub(ideal) & mask >= next.wrapping_sub(hole) & mask {
+                self.slots[hole] = self.slots[next].take();
+                hole = next;
+            }
+            next = (next + 1) & mask;
+        }
+        removed.value
+    }
+
+    /// Removes all of the values for which `f` returns false.
+    ///
+    /// If `f` panics, the values which have not been visited yet are kept.
+    pub(crate) fn retain(&mut self, mut f: impl FnMut(&mut T) -> bool) {
+        let start = match self.slots.iter().position(Option::is_none) {
+            Some(start) => start,
+            None => return,
+        };
+        // No probe sequence crosses an empty slot, so starting from one and re-placing each value in order means a
+        // value can only move to a slot which has already been visited.
+        let mut pass = RetainPass {
+            table: self,
+            start,
+            offset: 1,
+        };
+        while let Some(index) = pass.next_index() {
+            let keep = f(&mut pass.table.slots[index].as_mut().expect("Index of empty slot").value);
+            pass.offset += 1;
+            let bucket = pass.table.slots[index].take().expect("Index of empty slot");
+            if keep {
+                pass.table.place(bucket);
+            } else {
+                pass.table.len -= 1;
+                drop(bucket);
+            }
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+        self.len = 0;
+    }
+
+    /// Makes room for at least `additional` more values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new allocation size overflows `usize`.
+    #[inline]
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        if self.len.saturating_add(additional) > self.capacity() {
+            let capacity = self.len.checked_add(additional).expect("Capacity overflow");
+            self.resize(slots_for(capacity).expect("Capacity overflow"));
+        }
+    }
+
+    pub(crate) fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        if self.len.saturating_add(additional) > self.capacity() {
+            // On overflow, requesting `usize::MAX` slots produces the appropriate error.
+            let slots = self
+                .len
+                .checked_add(additional)
+                .and_then(slots_for)
+                .unwrap_or(usize::MAX);
+            let mut new_slots = Vec::new();
+            new_slots.try_reserve_exact(slots)?;
+            self.move_to(new_slots, slots);
+        }
+        Ok(())
+    }
+
+    /// Reduces the number of slots to the minimum needed to hold the larger of `min_capacity` and the current length.
+    pub(crate) fn shrink_to(&mut self, min_capacity: usize) {
+        let slots = slots_for(min_capacity.max(self.len)).expect("Capacity overflow");
+        if slots < self.slots.len() {
+            self.resize(slots);
+        }
+    }
+
+    fn resize(&mut self, slots: usize) {
+        self.move_to(Vec::with_capacity(slots), slots);
+    }
+
+    /// Moves all the values into `new_slots` (which is empty and has room for `slots` slots).
+    fn move_to(&mut self, mut new_slots: Vec<Option<Bucket<T>>>, slots: usize) {
+        new_slots.resize_with(slots, || None);
+        let old_slots = mem::replace(&mut self.slots, new_slots);
+        for bucket in old_slots.into_iter().flatten() {
+            self.place(bucket);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn iter(&self) -> RawIter<'_, T> {
+        RawIter {
+            slots: self.slots.iter(),
+            remaining: self.len,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn iter_mut(&mut self) -> RawIterMut<'_, T> {
+        RawIterMut {
+            slots: self.slots.iter_mut(),
+            remaining: self.len,
+        }
+    }
+
+    /// Removes all the values, keeping the allocated slots. Any values which are not iterated over are dropped when the
+    /// iterator is.
+    ///
+    /// The slots are moved out of the table until the iterator is dropped, so if it is leaked the table is left empty
+    /// (without its allocation) rather than holding values which have already been returned.
+    #[inline]
+    pub(crate) fn drain(&mut self) -> RawDrain<'_, T> {
+        let remaining = mem::replace(&mut self.len, 0);
+        let slots = mem::take(&mut self.slots);
+        RawDrain {
+            table: self,
+            slots,
+            index: 0,
+            remaining,
+        }
+    }
+}
+
+/// The state of a call to `RawTable::retain`. If it is dropped before every slot has been visited (because the
+/// predicate panicked) the remaining values are re-placed without calling the predicate, so that none of them is
+/// left unreachable behind a removed value.
+struct RetainPass<'a, T> {
+    table: &'a mut RawTable<T>,
+    start: usize,
+    offset: usize,
+}
+
+impl<T> RetainPass<'_, T> {
+    /// Returns the index of the next slot to visit, skipping empty slots.
+    #[inline]
+    fn next_index(&mut self) -> Option<usize> {
+        let mask = self.table.mask();
+        while self.offset < self.table.slots.len() {
+            let index = (self.start + self.offset) & mask;
+            if self.table.slots[index].is_some() {
+                return Some(index);
+            }
+            self.offset += 1;
+        }
+        None
+    }
+}
+
+impl<T> Drop for RetainPass<'_, T> {
+    fn drop(&mut self) {
+        while let Some(index) = self.next_index() {
+            self.offset += 1;
+            let bucket = self.table.slots[index].take().expect("Index of empty slot");
+            self.table.place(bucket);
+        }
+    }
+}
+
+impl<T> IntoIterator for RawTable<T> {
+    type Item = T;
+    type IntoIter = RawIntoIter<T>;
+
+    #[inline]
+    fn into_iter(self) -> RawIntoIter<T> {
+        RawIntoIter {
+            slots: self.slots.into_iter(),
+            remaining: self.len,
+        }
+    }
+}
+
+/// Generates an iterator over the values in the table, which yields `$item` for each occupied slot.
+macro_rules! raw_iter {
+    ($name:ident $(<$lifetime:lifetime>)?, $slots:ty, $item:ty, |$slot:ident| $take:expr) => {
+        pub(crate) struct $name<$($lifetime,)? T> {
+            slots: $slots,
+            remaining: usize,
+        }
+
+        impl<$($lifetime,)? T> Iterator for $name<$($lifetime,)? T> {
+            type Item = $item;
+
+            #[inline]
+            fn next(&mut self) -> Option<$item> {
+                for $slot in &mut self.slots {
+                    if let Some(bucket) = $take {
+                        self.remaining -= 1;
+                        return Some(bucket);
+                    }
+                }
+                None
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (self.remaining, Some(self.remaining))
+            }
+        }
+
+        impl<$($lifetime,)? T> ExactSizeIterator for $name<$($lifetime,)? T> {}
+
+        impl<$($lifetime,)? T> FusedIterator for $name<$($lifetime,)? T> {}
+    };
+}
+
+raw_iter!(RawIter<'a>, slice::Iter<'a, Option<Bucket<T>>>, &'a T, |slot| slot
+    .as_ref()
+    .map(|b| &b.value));
+raw_iter!(
+    RawIterMut<'a>,
+    slice::IterMut<'a, Option<Bucket<T>>>,
+    &'a mut T,
+    |slot| slot.as_mut().map(|b| &mut b.value)
+);
+raw_iter!(RawIntoIter, alloc::vec::IntoIter<Option<Bucket<T>>>, T, |slot| slot
+    .map(|b| b.value));
+
+impl<T> Clone for RawIter<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        RawIter {
+            slots: self.slots.clone(),
+            remaining: self.remaining,
+        }
+    }
+}
+
+pub(crate) struct RawDrain<'a, T> {
+    table: &'a mut RawTable<T>,
+    slots: Vec<Option<Bucket<T>>>,
+    index: usize,
+    remaining: usize,
+}
+
+impl<T> Iterator for RawDrain<'_, T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        while let Some(slot) = self.slots.get_mut(self.index) {
+            self.index += 1;
+            if let Some(bucket) = slot.take() {
+                self.remaining -= 1;
+                return Some(bucket.value);
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for RawDrain<'_, T> {}
+
+impl<T> FusedIterator for RawDrain<'_, T> {}
+
+impl<T> Drop for RawDrain<'_, T> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+        // Every slot is now empty, so the allocation can be given back to the table.
+        self.table.slots = mem::take(&mut self.slots);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn find(table: &RawTable<u32>, hash: u64, value: u32) -> Option<usize> {
+        table.find(hash, |v| *v == value)
+    }
+
+    #[test]
+    fn test_remove_shifts_back_colliding_values() {
+        let mut table = RawTable::new();
+        // All of these have the same ideal slot, including one which wraps around to the start.
+        for value in 0..5 {
+            table.insert(14, value);
+        }
+        table.insert(15, 5);
+        table.insert(0, 6);
+        assert_eq!(16, table.slots.len());
+        for removed in 0..5 {
+            let index = find(&table, 14, removed).unwrap();
+            assert_eq!(removed, table.remove(index));
+            for value in removed + 1..5 {
+                assert!(
+                    find(&table, 14, value).is_some(),
+                    "lost {} after removing {}",
+                    value,
+                    removed
+                );
+            }
+            assert!(find(&table, 15, 5).is_some());
+            assert!(find(&table, 0, 6).is_some());
+        }
+        assert_eq!(2, table.len());
+    }
+
+    #[test]
+    fn test_retain_with_wrap_around() {
+        let mut table = RawTable::new();
+        for value in 0..7 {
+            table.insert(13 + value as u64 % 3, value);
+        }
+        let mut visited = Vec::new();
+        table.retain(|v| {
+            visited.push(*v);
+            *v % 2 == 0
+        });
+        visited.sort_unstable();
+        assert_eq!((0..7).collect::<Vec<_>>(), visited);
+        assert_eq!(4, table.len());
+        for value in 0..7 {
+            assert_eq!(value % 2 == 0, find(&table, 13 + value as u64 % 3, value).is_some());
+        }
+    }
+
+    #[test]
+    fn test_capacity() {
+        assert_eq!(Some(0), slots_for(0));
+        assert_eq!(Some(8), slots_for(1));
+        assert_eq!(Some(8), slots_for(5));
+        assert_eq!(Some(16), slots_for(6));
+        assert_eq!(Some(16), slots_for(10));
+        assert_eq!(Some(32), slots_for(11));
+        assert_eq!(None, slots_for(usize::MAX));
+        let mut table: RawTable<u32> = RawTable::with_capacity(100);
+        assert!(table.capacity() >= 100);
+        assert!(table.try_reserve(usize::MAX).is_err());
+        table.shrink_to(0);
+        assert_eq!(0, table.capacity());
+    }
+
+    #[test]
+    fn test_drain_drops_remaining() {
+        let mut table = RawTable::new();
+        for value in 0..10 {
+            table.insert(value as u64, value);
+        }
+        assert_eq!(10, table.drain().len());
+        {
+            let mut drain = table.drain();
+            assert_eq!(0, drain.len());
+            assert_eq!(None, drain.next());
+        }
+        for value in 0..10 {
+            table.insert(value as u64, value);
+        }
+        let first = table.drain().next();
+        assert!(first.is_some());
+        assert_eq!(0, table.len());
+        assert_eq!(0, table.iter().count());
+        assert!(table.capacity() >= 10);
+    }
+
+    #[test]
+    fn test_leaked_drain_leaves_table_empty() {
+        let mut table = RawTable::new();
+        for value in 0..10 {
+            table.insert(value as u64, value);
+        }
+        let mut drain = table.drain();
+        assert!(drain.next().is_some());
+        mem::forget(drain);
+        assert_eq!(0, table.len());
+        assert_eq!(0, table.iter().count());
+        assert_eq!(None, find(&table, 1, 1));
+        let index = table.insert(1, 1);
+        assert_eq!(Some(index), find(&table, 1, 1));
+        assert_eq!(1, table.len());
+    }
+
+    #[test]
+    fn test_retain_panic_keeps_table_consistent() {
+        let mut table = RawTable::new();
+        // Several runs of colliding values, so a removal before the panic leaves values to be shifted back.
+        for value in 0..10 {
+            table.insert(value as u64 % 4 * 4, value);
+        }
+        let mut visited = 0;
+        let mut removed = 0;
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            table.retain(|v| {
+                visited += 1;
+                if visited == 6 {
+                    panic!("predicate panicked");
+                }
+                removed += *v as usize % 2;
+                *v % 2 == 0
+            })
+        }));
+        assert!(result.is_err());
+        assert!(removed > 0);
+        assert_eq!(10 - removed, table.len());
+        assert_eq!(table.len(), table.iter().count());
+        for value in table.iter().copied().collect::<Vec<u32>>() {
+            assert!(find(&table, value as u64 % 4 * 4, value).is_some(), "lost {}", value);
+        }
+    }
+}
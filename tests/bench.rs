@@ -1,8 +1,11 @@
-use ahash::{AHasher, CallHasher};
+// Criterion's ParameterizedBenchmark API, which these benchmarks use, is deprecated.
+#![allow(deprecated)]
+
+use ahash::{AHasher, CallHasher, RandomState};
 use criterion::*;
 use fxhash::FxHasher;
 use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+use std::hash::{BuildHasher, Hash, Hasher};
 
 #[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "aes"))]
 fn aeshash<H: Hash>(b: &H) -> u64 {
@@ -212,6 +215,28 @@ fn bench_sip(c: &mut Criterion) {
     );
 }
 
+fn bench_batch(c: &mut Criterion) {
+    let state = RandomState::with_seeds(1, 2);
+    let keys: Vec<u64> = (0..1024).collect();
+    let mut out = vec![0; keys.len()];
+    c.bench_function("batch u64 individual", |b| {
+        b.iter(|| {
+            for (key, out) in keys.iter().zip(out.iter_mut()) {
+                let mut hasher = state.build_hasher();
+                hasher.write_u64(*key);
+                *out = hasher.finish();
+            }
+            black_box(&out);
+        })
+    });
+    c.bench_function("batch u64 slice", |b| {
+        b.iter(|| {
+            state.hash_u64_slice(&keys, &mut out);
+            black_box(&out);
+        })
+    });
+}
+
 criterion_main!(benches);
 criterion_group!(
     benches,
@@ -220,5 +245,6 @@ criterion_group!(
     bench_fx,
     bench_fnv,
     bench_sea,
-    bench_sip
+    bench_sip,
+    bench_batch
 );
@@ -159,7 +159,7 @@ fn hash<T: Hasher>(b: &impl Hash, hasher: &dyn Fn() -> T) -> u64 {
 #[test]
 fn test_bucket_distribution() {
     let hasher = || AHasher::new_with_keys(123456789, 987654321);
-    test_hash_common_words(&hasher);
+    test_hash_common_words(hasher);
     let sequence: Vec<_> = (0..320000).collect();
     check_for_collisions(&hasher, &sequence, 32);
     let sequence: Vec<_> = (0..2560000).collect();
@@ -170,6 +170,213 @@ fn test_bucket_distribution() {
     check_for_collisions(&hasher, &sequence, 256);
 }
 
+/// Tests of the `AHashMap` and `AHashSet` API, which is the same with either the `std` or `alloc` feature.
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod collections {
+    #[allow(unused)] // False positive (The tests are not compiled when this file is built as a benchmark)
+    use ahash::{ahashmap, ahashset, AHashMap, AHashSet, RandomState};
+
+    #[test]
+    fn test_map_insert_get_remove() {
+        let mut m: AHashMap<u64, u64> = AHashMap::new();
+        for i in 0..10_000 {
+            assert_eq!(None, m.insert(i, i * 2));
+        }
+        assert_eq!(10_000, m.len());
+        for i in 0..10_000 {
+            assert_eq!(Some(&(i * 2)), m.get(&i));
+            assert_eq!(i * 2, m[&i]);
+        }
+        assert_eq!(Some(2), m.insert(1, 3));
+        *m.get_mut(&1).unwrap() -= 1;
+        assert_eq!(Some((&1, &2)), m.get_key_value(&1));
+        for i in (0..10_000).step_by(2) {
+            assert_eq!(Some(i * 2), m.remove(&i));
+        }
+        assert_eq!(5_000, m.len());
+        for i in 0..10_000 {
+            assert_eq!(i % 2 == 1, m.contains_key(&i), "{}", i);
+        }
+        assert_eq!(Some((1, 2)), m.remove_entry(&1));
+        assert_eq!(None, m.remove(&1));
+    }
+
+    #[test]
+    fn test_map_borrowed_keys() {
+        let mut m: AHashMap<String, usize> = AHashMap::with_capacity(2);
+        m.insert("foo".to_string(), 3);
+        m.insert("bar".to_string(), 4);
+        assert_eq!(Some(&3), m.get("foo"));
+        assert!(m.contains_key("bar"));
+        assert!(!m.contains_key("baz"));
+        assert_eq!(Some(4), m.remove("bar"));
+    }
+
+    #[test]
+    fn test_map_entry() {
+        let mut counts: AHashMap<&str, u32> = AHashMap::new();
+        for word in "a b c a b a".split(' ') {
+            *counts.entry(word).or_insert(0) += 1;
+        }
+        assert_eq!(ahashmap! { "a" => 3, "b" => 2, "c" => 1 }, counts);
+        counts.entry("a").and_modify(|v| *v *= 10).or_default();
+        counts.entry("d").and_modify(|v| *v *= 10).or_default();
+        assert_eq!(30, counts["a"]);
+        assert_eq!(0, counts["d"]);
+        assert_eq!(&"e", counts.entry("e").key());
+        assert_eq!(5, *counts.entry("e").or_insert_with_key(|k| k.len() as u32 + 4));
+        assert_eq!(5, *counts.entry("e").or_insert_with(|| unreachable!()));
+    }
+
+    #[test]
+    fn test_map_iterators() {
+        let mut m: AHashMap<u32, u32> = (0..100).map(|i| (i, i)).collect();
+        assert_eq!(100, m.iter().len());
+        assert_eq!((0..100).sum::<u32>(), m.keys().sum::<u32>());
+        for (_, v) in m.iter_mut() {
+            *v += 1;
+        }
+        for v in m.values_mut() {
+            *v *= 2;
+        }
+        for (k, v) in &m {
+            assert_eq!((k + 1) * 2, *v);
+        }
+        assert_eq!((1..101).map(|i| i * 2).sum::<u32>(), m.values().sum::<u32>());
+        assert_eq!((0..100).sum::<u32>(), m.clone().into_keys().sum::<u32>());
+        assert_eq!(
+            (1..101).map(|i| i * 2).sum::<u32>(),
+            m.clone().into_values().sum::<u32>()
+        );
+        let mut pairs: Vec<_> = m.into_iter().collect();
+        pairs.sort_unstable();
+        assert_eq!((0..100).map(|i| (i, (i + 1) * 2)).collect::<Vec<_>>(), pairs);
+    }
+
+    #[test]
+    fn test_map_retain_drain_clear() {
+        let mut m: AHashMap<u32, u32> = (0..1000).map(|i| (i, i)).collect();
+        m.retain(|k, v| {
+            *v += 1;
+            k % 3 == 0
+        });
+        assert_eq!(334, m.len());
+        assert!(m.iter().all(|(k, v)| k % 3 == 0 && *v == k + 1));
+        for i in 0..1000 {
+            assert_eq!(i % 3 == 0, m.contains_key(&i));
+        }
+        let capacity = m.capacity();
+        let mut drained: Vec<_> = m.drain().map(|(k, _)| k).collect();
+        drained.sort_unstable();
+        assert_eq!((0..1000).step_by(3).collect::<Vec<_>>(), drained);
+        assert!(m.is_empty());
+        assert!(m.capacity() >= capacity);
+        m.insert(1, 1);
+        m.clear();
+        assert!(m.is_empty());
+        assert_eq!(None, m.get(&1));
+    }
+
+    #[test]
+    fn test_map_capacity() {
+        let mut m: AHashMap<u32, u32> = AHashMap::with_capacity(100);
+        assert!(m.capacity() >= 100);
+        m.extend((0..100).map(|i| (i, i)));
+        m.reserve(100);
+        let capacity = m.capacity();
+        assert!(capacity >= 200);
+        m.extend((100..200).map(|i| (i, i)));
+        assert_eq!(capacity, m.capacity());
+        assert!(m.try_reserve(usize::MAX).is_err());
+        m.retain(|k, _| *k < 10);
+        m.shrink_to(50);
+        assert!(m.capacity() >= 50);
+        m.shrink_to_fit();
+        assert!(m.capacity() >= 10);
+        assert_eq!(10, m.len());
+        assert!((0..10).all(|i| m[&i] == i));
+    }
+
+    #[test]
+    fn test_map_equality_and_hasher() {
+        let state = RandomState::with_seeds(1, 2);
+        let a = ahashmap! { @hasher state.clone(), 1 => 'a', 2 => 'b' };
+        let mut b = AHashMap::with_capacity_and_hasher(0, state.clone());
+        b.extend([(&2, &'b'), (&1, &'a')]);
+        assert_eq!(a, b);
+        b.insert(3, 'c');
+        assert_ne!(a, b);
+        assert_eq!(state.hash_one(1), b.hasher().hash_one(1));
+        assert_eq!(AHashMap::from([(1, 'a'), (2, 'b')]), a.clone().into_iter().collect());
+        assert_eq!(AHashMap::<u8, u8>::default(), AHashMap::new());
+        let debug = format!("{:?}", ahashmap! { 1 => 2 });
+        assert_eq!("{1: 2}", debug);
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let mut s: AHashSet<i32> = AHashSet::new();
+        assert!(s.insert(1));
+        assert!(!s.insert(1));
+        assert!(s.contains(&1));
+        assert_eq!(Some(&1), s.get(&1));
+        assert_eq!(None, s.replace(2));
+        assert_eq!(Some(2), s.replace(2));
+        assert!(s.remove(&2));
+        assert_eq!(Some(1), s.take(&1));
+        assert!(s.is_empty());
+
+        let a = ahashset! { 1, 3, 5, 9, 11, 16, 19, 24 };
+        let b = AHashSet::from([-2, 1, 5, 9, 13, 19]);
+        let sorted = |iter: &mut dyn Iterator<Item = &i32>| {
+            let mut v: Vec<i32> = iter.cloned().collect();
+            v.sort_unstable();
+            v
+        };
+        assert_eq!(vec![1, 5, 9, 19], sorted(&mut a.intersection(&b)));
+        assert_eq!(vec![3, 11, 16, 24], sorted(&mut a.difference(&b)));
+        assert_eq!(vec![-2, 3, 11, 13, 16, 24], sorted(&mut a.symmetric_difference(&b)));
+        assert_eq!(vec![-2, 1, 3, 5, 9, 11, 13, 16, 19, 24], sorted(&mut a.union(&b)));
+        assert_eq!(vec![1, 5, 9, 19], sorted(&mut (&a & &b).iter()));
+        assert_eq!(vec![3, 11, 16, 24], sorted(&mut (&a - &b).iter()));
+        assert_eq!(vec![-2, 3, 11, 13, 16, 24], sorted(&mut (&a ^ &b).iter()));
+        assert_eq!(10, (&a | &b).len());
+
+        let small = ahashset! { 1, 5 };
+        assert!(small.is_subset(&a));
+        assert!(a.is_superset(&small));
+        assert!(!a.is_subset(&small));
+        assert!(!small.is_disjoint(&b));
+        assert!(ahashset! { 100 }.is_disjoint(&a));
+    }
+
+    #[test]
+    fn test_set_iterators_and_capacity() {
+        let mut s: AHashSet<u32> = (0..100).collect();
+        assert_eq!(100, s.iter().len());
+        assert_eq!((0..100).sum::<u32>(), (&s).into_iter().sum::<u32>());
+        s.retain(|v| v % 2 == 0);
+        assert_eq!(50, s.len());
+        s.extend(&[1, 3]);
+        s.extend(vec![5]);
+        assert_eq!(53, s.len());
+        s.reserve(100);
+        assert!(s.capacity() >= 153);
+        assert!(s.try_reserve(usize::MAX).is_err());
+        s.shrink_to_fit();
+        assert!(s.capacity() >= 53);
+        let mut drained: Vec<_> = s.drain().collect();
+        drained.sort_unstable();
+        assert_eq!(53, drained.len());
+        assert!(s.is_empty());
+        s.insert(7);
+        assert_eq!(vec![7], s.clone().into_iter().collect::<Vec<_>>());
+        s.clear();
+        assert_eq!(AHashSet::default(), s);
+        assert_eq!("{7}", format!("{:?}", ahashset! { 7 }));
+    }
+}
+
 fn ahash_vec<H: Hash>(b: &Vec<H>) -> u64 {
     let mut total: u64 = 0;
     for item in b {